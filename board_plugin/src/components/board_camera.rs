@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use bevy::render::camera::CameraPlugin;
+
+/// Marks the 2D camera showing the board, used for picking and moved by the camera controls
+#[derive(Debug, Copy, Clone, Component)]
+pub struct BoardCamera;
+
+impl BoardCamera {
+    /// Camera picking the board tiles among `cameras`: the one marked as `BoardCamera`, or the
+    /// only 2D camera when none is marked
+    pub fn picking_camera<'a>(
+        cameras: impl Iterator<Item = (&'a Camera, &'a GlobalTransform, Option<&'a BoardCamera>)>,
+    ) -> Option<(&'a Camera, &'a GlobalTransform)> {
+        let mut cameras_2d = Vec::new();
+        for (camera, transform, board_camera) in cameras {
+            if board_camera.is_some() {
+                return Some((camera, transform));
+            }
+            if camera.name.as_deref() == Some(CameraPlugin::CAMERA_2D) {
+                cameras_2d.push((camera, transform));
+            }
+        }
+        match cameras_2d.len() {
+            1 => cameras_2d.pop(),
+            _ => None,
+        }
+    }
+
+    /// Translates a window cursor `position` to world space through a camera
+    pub fn cursor_to_world(
+        window: &Window,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        position: Vec2,
+    ) -> Vec2 {
+        // Window to normalized device coordinates
+        let window_size = Vec2::new(window.width(), window.height());
+        let ndc = (position / window_size) * 2. - Vec2::ONE;
+        // Normalized device coordinates to world space
+        let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix.inverse();
+        ndc_to_world.project_point3(ndc.extend(-1.)).truncate()
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::prelude::Component;
+
+/// Flag left over a bomb which was uncovered without ending the game
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct ExplodedFlag;
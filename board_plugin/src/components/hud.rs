@@ -0,0 +1,25 @@
+use bevy::prelude::Component;
+
+/// Root node of the game HUD
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct Hud;
+
+/// HUD text displaying the remaining mine counter
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct MineCounterText;
+
+/// HUD text displaying the game timer
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct TimerText;
+
+/// HUD text displaying the remaining lives
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct LivesText;
+
+/// HUD button switching the touch tap action between reveal and flag
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct TapModeButton;
+
+/// HUD text displaying the current touch tap action
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct TapModeText;
@@ -1,9 +1,25 @@
+pub use board_camera::BoardCamera;
 pub use bomb::Bomb;
 pub use bomb_neighbor::BombNeighbor;
 pub use coordinates::Coordinates;
-pub use uncover::Uncover;
+pub use exploded_flag::ExplodedFlag;
+pub use hud::*;
+pub use particle::Particle;
+pub use themed::Themed;
+pub use tile_cursor::TileCursor;
+pub use tile_highlight::*;
+pub use tween::*;
+pub use uncover::*;
 
+mod board_camera;
 mod bomb;
 mod bomb_neighbor;
 mod coordinates;
+mod exploded_flag;
+mod hud;
+mod particle;
+mod themed;
+mod tile_cursor;
+mod tile_highlight;
+mod tween;
 mod uncover;
@@ -0,0 +1,20 @@
+use bevy::prelude::{Component, Vec2};
+
+/// Short lived sprite moved by the particle system, despawned at the end of its lifetime
+#[derive(Debug, Clone, Component)]
+pub struct Particle {
+    /// Velocity, in world units per second
+    pub velocity: Vec2,
+    /// Downward acceleration, in world units per second squared
+    pub gravity: f32,
+    /// Rotation speed, in radians per second
+    pub spin: f32,
+    /// Size multiplier reached at the end of the particle life
+    pub growth: f32,
+    /// Lifetime, in seconds
+    pub lifetime: f32,
+    /// Seconds since the particle was spawned
+    pub age: f32,
+    /// Initial color alpha, faded out over the particle life
+    pub alpha: f32,
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::Component;
+
+/// Board asset displayed by an entity, used to restyle spawned entities when the theme changes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Component)]
+pub enum Themed {
+    /// Board background sprite
+    Board,
+    /// Uncovered tile sprite
+    Tile,
+    /// Tile cover sprite
+    CoveredTile,
+    /// Flag sprite
+    Flag,
+    /// Flag of a tile without bomb, shown once the game is lost
+    WrongFlag,
+    /// Bomb sprite
+    Bomb,
+    /// Flag of a bomb exploded without ending the game
+    Exploded,
+    /// Bomb counter text, with its value
+    BombCounter(u8),
+    /// Keyboard tile cursor
+    Cursor,
+    /// Mouse hover highlight
+    Hover,
+    /// Covered tile drawn as pressed while a mouse button is held over it
+    Pressed,
+}
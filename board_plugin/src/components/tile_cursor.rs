@@ -0,0 +1,8 @@
+use crate::components::Coordinates;
+use bevy::prelude::Component;
+
+/// Keyboard tile cursor, highlighting the tile targeted by the keyboard actions
+#[derive(Debug, Copy, Clone, Component)]
+pub struct TileCursor {
+    pub coordinates: Coordinates,
+}
@@ -0,0 +1,16 @@
+use crate::components::Coordinates;
+use bevy::prelude::Component;
+
+/// Highlight of the covered tile under the mouse cursor
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct HoverHighlight;
+
+/// Pressed look of a covered tile, shown while a mouse button is held over the tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct PressedTile {
+    pub coordinates: Coordinates,
+}
+
+/// Dot of the tile cursor path drawn while playing a replay back
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct CursorPathDot;
@@ -0,0 +1,49 @@
+use bevy::prelude::{Component, Vec3};
+
+/// Transform animation of an entity, played by the animation systems
+#[derive(Debug, Clone, Component)]
+pub struct Tween {
+    /// Animation kind
+    pub kind: TweenKind,
+    /// Seconds before the animation starts
+    pub delay: f32,
+    /// Animation length in seconds
+    pub duration: f32,
+    /// Seconds since the tween was added, delay included
+    pub elapsed: f32,
+}
+
+/// Transform change applied by a `Tween`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TweenKind {
+    /// Shrinks the entity then despawns it
+    ShrinkOut,
+    /// Grows the entity from a null scale with a small overshoot
+    PopIn,
+    /// Shakes the entity with a decreasing `amplitude`
+    Shake {
+        amplitude: f32,
+        /// Translation offset currently applied
+        offset: Vec3,
+    },
+}
+
+impl Tween {
+    /// Creates a tween starting after `delay` seconds
+    pub fn new(kind: TweenKind, delay: f32, duration: f32) -> Self {
+        Self {
+            kind,
+            delay,
+            duration,
+            elapsed: 0.,
+        }
+    }
+
+    /// Animation progress, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0. {
+            return 1.;
+        }
+        ((self.elapsed - self.delay) / self.duration).clamp(0., 1.)
+    }
+}
@@ -3,4 +3,11 @@ use bevy::prelude::Component;
 /// Uncover component, indicates a covered tile that should be uncovered
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
-pub struct Uncover;
+pub struct Uncover {
+    /// History id of the move uncovering the tile, `None` for the safe start
+    pub move_id: Option<u64>,
+}
+
+/// Cascade depth of a tile cover to uncover, from the triggered tile
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct RevealDepth(pub u16);
@@ -1,4 +1,6 @@
 use crate::components::Coordinates;
+use crate::resources::{BoardOptions, MoveAction};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone)]
 pub struct TileTriggerEvent(pub Coordinates);
@@ -6,8 +8,75 @@ pub struct TileTriggerEvent(pub Coordinates);
 #[derive(Debug, Copy, Clone)]
 pub struct BoardCompletedEvent;
 
+/// A bomb exploded and ended the game
 #[derive(Debug, Copy, Clone)]
-pub struct BombExplosionEvent;
+pub struct BombExplosionEvent(pub Coordinates);
+
+/// A bomb exploded and cost a life
+#[derive(Debug, Copy, Clone)]
+pub struct LifeLostEvent {
+    pub coordinates: Coordinates,
+    pub remaining: u8,
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct TileMarkEvent(pub Coordinates);
+
+/// Reveals the unflagged neighbors of an uncovered bomb counter whose flags match its count
+#[derive(Debug, Copy, Clone)]
+pub struct TileChordEvent(pub Coordinates);
+
+#[derive(Debug, Copy, Clone)]
+pub struct UndoEvent;
+
+#[derive(Debug, Copy, Clone)]
+pub struct RedoEvent;
+
+/// Step of a change of the board state, sent as it is applied so the game can be journaled
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BoardChangeEvent {
+    /// A move was registered in the history, a mark is toggled with it
+    MoveStarted(MoveAction),
+    /// A tile was uncovered by the move `move_id` of the history, `None` for the safe start
+    TileUncovered {
+        coordinates: Coordinates,
+        move_id: Option<u64>,
+    },
+    /// The last move was undone
+    Undone,
+    /// The last undone move was redone
+    Redone,
+}
+
+/// Replaces the current board, keeping its bomb layout if `same_layout` is set
+#[derive(Debug, Copy, Clone)]
+pub struct ResetBoard {
+    pub same_layout: bool,
+}
+
+/// Replaces the current board with a new one generated from the given options
+#[derive(Debug, Clone)]
+pub struct NewGame(pub BoardOptions);
+
+/// Finishes the running board animations
+#[derive(Debug, Copy, Clone)]
+pub struct SkipAnimations;
+
+/// Action applied to the tile cursor by the keyboard or a gamepad
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CursorAction {
+    /// Moves the cursor by one tile
+    Move { x: i8, y: i8 },
+    /// Moves the cursor to the board edge in a direction
+    MoveToEdge { x: i8, y: i8 },
+    /// Jumps to the next covered tile in reading order
+    NextCoveredTile,
+    /// Jumps to the previous covered tile in reading order
+    PreviousCoveredTile,
+    /// Reveals the tile under the cursor
+    Reveal,
+    /// Flags the tile under the cursor
+    Flag,
+    /// Chords the tile under the cursor
+    Chord,
+}
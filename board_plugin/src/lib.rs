@@ -1,6 +1,9 @@
+// Bevy systems take their resources and queries as arguments
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 mod bounds;
 pub mod components;
-mod events;
+pub mod events;
 pub mod resources;
 mod systems;
 
@@ -9,22 +12,39 @@ use bevy::ecs::schedule::StateData;
 use bevy::log;
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
-use bevy::utils::{AHashExt, HashMap};
+use bevy::transform::TransformSystem;
+use bevy::utils::{AHashExt, HashMap, HashSet};
+use bevy::window::{WindowId, WindowResized, WindowScaleFactorChanged};
 use bounds::Bounds2;
 use components::*;
 use resources::tile::Tile;
 use resources::tile_map::TileMap;
 use resources::Board;
 use resources::BoardAssets;
+use resources::BoardLayout;
 use resources::BoardOptions;
 use resources::BoardPosition;
+use resources::BombRule;
+use resources::GameStatus;
+use resources::InputBindings;
+use resources::Playback;
+use resources::Replay;
+use resources::SavedGame;
+use resources::TapMode;
+use resources::TilePointer;
 use resources::TileSize;
+use resources::{BoardTheme, BoardThemeLoader};
+use std::time::Duration;
+use systems::hud::HUD_HEIGHT;
+use systems::playback::PlaybackDriver;
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::InspectableRegistry;
 
 pub struct BoardPlugin<T> {
     pub running_state: T,
+    /// State playing the `Playback` resource replay back on the board, without player input
+    pub playback_state: Option<T>,
 }
 
 impl<T: StateData> Plugin for BoardPlugin<T> {
@@ -43,82 +63,338 @@ impl<T: StateData> Plugin for BoardPlugin<T> {
 
         // When the running states comes into the stack we load a board
         app.add_system_set(
-            SystemSet::on_enter(self.running_state.clone()).with_system(Self::create_board),
+            SystemSet::on_enter(self.running_state.clone())
+                .with_system(Self::create_board)
+                .with_system(systems::hud::spawn_hud),
         )
         // We handle input and trigger events only if the state is active
         .add_system_set(
             SystemSet::on_update(self.running_state.clone())
                 .with_system(systems::input::input_handling)
-                .with_system(systems::uncover::trigger_event_handler),
-        )
-        // We handle uncovering even if the state is inactive
-        .add_system_set(
-            SystemSet::on_in_stack_update(self.running_state.clone())
+                .with_system(systems::highlight::update_tile_highlights)
+                .with_system(systems::camera::camera_controls)
+                .with_system(systems::cursor::keyboard_cursor)
+                .with_system(systems::gamepad::gamepad_cursor)
+                .with_system(systems::touch::touch_input)
+                .with_system(systems::camera::touch_camera)
+                .with_system(systems::hud::toggle_tap_mode)
+                .with_system(systems::cursor::apply_cursor_actions)
+                .with_system(systems::uncover::trigger_event_handler)
+                .with_system(systems::history::undo_moves)
+                .with_system(systems::history::redo_moves)
+                .with_system(systems::hud::tick_timer)
+                // Pending uncovering is held while the state is inactive
                 .with_system(systems::uncover::uncover_tiles)
-                .with_system(systems::mark::mark_tiles),
+                .with_system(systems::mark::mark_tiles)
+                .with_system(systems::replay::record_replay),
         )
+        // We keep the HUD and board resets running even if the state is inactive
+        .add_system_set(Self::in_stack_systems(self.running_state.clone()))
         .add_system_set(
-            SystemSet::on_exit(self.running_state.clone()).with_system(Self::cleanup_board),
+            SystemSet::on_exit(self.running_state.clone())
+                .with_system(Self::cleanup_board)
+                .with_system(systems::hud::despawn_hud),
+        )
+        // The touch tap mode, the input bindings and the mouse pointer are kept across games
+        .init_resource::<TapMode>()
+        .init_resource::<InputBindings>()
+        .init_resource::<TilePointer>()
+        // Themes are applied in every state
+        .add_asset::<BoardTheme>()
+        .init_asset_loader::<BoardThemeLoader>()
+        .init_resource::<BoardAssets>()
+        .add_system(systems::theme::apply_theme)
+        .add_system(systems::theme::restyle_board)
+        .add_system(systems::theme::pixelate_atlas)
+        // Animations and particles are played in every state
+        .add_system(systems::particles::update_particles)
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            systems::animation::animate.before(TransformSystem::TransformPropagate),
         )
         .add_event::<TileTriggerEvent>()
         .add_event::<TileMarkEvent>()
+        .add_event::<TileChordEvent>()
         .add_event::<BombExplosionEvent>()
-        .add_event::<BoardCompletedEvent>();
+        .add_event::<LifeLostEvent>()
+        .add_event::<BoardCompletedEvent>()
+        .add_event::<ResetBoard>()
+        .add_event::<NewGame>()
+        .add_event::<UndoEvent>()
+        .add_event::<RedoEvent>()
+        .add_event::<BoardChangeEvent>()
+        .add_event::<SkipAnimations>()
+        .add_event::<CursorAction>();
+
+        // The played back replay drives the board instead of the player input
+        if let Some(playback_state) = &self.playback_state {
+            app.add_system_set(
+                SystemSet::on_enter(playback_state.clone())
+                    .with_system(Self::create_board)
+                    .with_system(systems::hud::spawn_hud),
+            )
+            .add_system_set(
+                SystemSet::on_update(playback_state.clone())
+                    .with_system(systems::playback::play_replay.label(PlaybackDriver))
+                    .with_system(systems::playback::draw_cursor_path)
+                    .with_system(systems::camera::camera_controls)
+                    .with_system(systems::camera::touch_camera)
+                    .with_system(systems::uncover::trigger_event_handler.after(PlaybackDriver))
+                    .with_system(systems::history::undo_moves.after(PlaybackDriver))
+                    .with_system(systems::history::redo_moves.after(PlaybackDriver))
+                    .with_system(systems::uncover::uncover_tiles)
+                    .with_system(systems::mark::mark_tiles.after(PlaybackDriver)),
+            )
+            .add_system_set(Self::in_stack_systems(playback_state.clone()))
+            .add_system_set(
+                SystemSet::on_exit(playback_state.clone())
+                    .with_system(Self::cleanup_board)
+                    .with_system(Self::cleanup_playback)
+                    .with_system(systems::hud::despawn_hud),
+            );
+        }
+
+        // Winit sends no touches on the web, they are read from the canvas instead
+        #[cfg(target_arch = "wasm32")]
+        app.init_resource::<systems::web_touch::WebTouches>()
+            .add_startup_system(systems::web_touch::listen_web_touches)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                systems::web_touch::send_web_touches.before(bevy::input::InputSystem),
+            );
 
         log::info!("Loaded Board Plugin");
     }
 }
 
 impl<T> BoardPlugin<T> {
+    /// Board systems running while `state` is in the stack
+    fn in_stack_systems(state: T) -> SystemSet
+    where
+        T: StateData,
+    {
+        SystemSet::on_in_stack_update(state)
+            .with_system(systems::hud::update_status)
+            .with_system(systems::hud::update_hud)
+            .with_system(systems::hud::update_tap_mode_label)
+            .with_system(Self::reset_board)
+            .with_system(Self::relayout_board)
+            .with_system(systems::mark::update_wrong_flags)
+            .with_system(systems::animation::shake_board)
+            .with_system(systems::particles::explosion_particles)
+            .with_system(systems::particles::victory_confetti)
+    }
+
     fn cleanup_board(board: Res<Board>, mut commands: Commands) {
         commands.entity(board.entity).despawn_recursive();
         commands.remove_resource::<Board>();
     }
 
-    /// System to generate the complete board
+    fn cleanup_playback(mut commands: Commands) {
+        commands.remove_resource::<Playback>();
+    }
+
+    /// System to generate the complete board, or to resume the `SavedGame` resource if any.
+    /// The bombs are laid as in the `BoardLayout` resource if any.
+    ///
+    /// In the playback state the board of the `Playback` replay is spawned instead
     pub fn create_board(
         mut commands: Commands,
         board_options: Option<Res<BoardOptions>>,
+        saved_game: Option<Res<SavedGame>>,
+        layout: Option<Res<BoardLayout>>,
+        playback: Option<Res<Playback>>,
         board_assets: Res<BoardAssets>,
-        window: Res<WindowDescriptor>,
+        windows: Res<Windows>,
     ) {
-        let options = match board_options {
+        let window_size = Self::window_size(&windows);
+        if let Some(playback) = playback {
+            log::info!("playing back replay");
+            // The replay options are kept by the board, the player's ones are left alone
+            Self::spawn_board(
+                &mut commands,
+                &playback.replay.options,
+                playback.replay.tile_map.clone(),
+                &board_assets,
+                window_size,
+                None,
+                None,
+            );
+            return;
+        }
+        if let Some(saved_game) = saved_game {
+            log::info!("resuming saved game");
+            commands.remove_resource::<SavedGame>();
+            Self::spawn_board(
+                &mut commands,
+                &saved_game.options,
+                saved_game.tile_map.clone(),
+                &board_assets,
+                window_size,
+                Some(&saved_game),
+                saved_game.replay.clone(),
+            );
+            return;
+        }
+        let mut options = match board_options {
             None => BoardOptions::default(), // If no options is set we use the default one
             Some(o) => o.clone(),
         };
+        let tile_map = match layout {
+            Some(layout) => {
+                log::info!("spawning board layout");
+                commands.remove_resource::<BoardLayout>();
+                let BoardLayout(tile_map) = layout.clone();
+                options.map_size = (tile_map.width(), tile_map.height());
+                options.bomb_count = tile_map.bomb_count();
+                tile_map
+            }
+            None => Self::generate_tile_map(&options),
+        };
+        let replay = Replay::new(options.clone(), tile_map.clone());
+        Self::spawn_board(
+            &mut commands,
+            &options,
+            tile_map,
+            &board_assets,
+            window_size,
+            None,
+            Some(replay),
+        );
+    }
+
+    /// System handling the `ResetBoard` and `NewGame` events by replacing the current board.
+    ///
+    /// A reset board keeps the options of the current one
+    fn reset_board(
+        mut commands: Commands,
+        board: Res<Board>,
+        board_assets: Res<BoardAssets>,
+        windows: Res<Windows>,
+        playback: Option<Res<Playback>>,
+        mut reset_board_evr: EventReader<ResetBoard>,
+        mut new_game_evr: EventReader<NewGame>,
+    ) {
+        let mut options = board.options.clone();
+        // `None` means a new layout must be generated
+        let mut layout = None;
+        let mut requested = false;
+        for event in reset_board_evr.iter() {
+            requested = true;
+            layout = event.same_layout.then(|| board.tile_map.clone());
+        }
+        for NewGame(new_options) in new_game_evr.iter() {
+            requested = true;
+            layout = None;
+            options = new_options.clone();
+            commands.insert_resource(new_options.clone());
+        }
+        if !requested {
+            return;
+        }
+        log::info!("reseting board");
+        commands.entity(board.entity).despawn_recursive();
+        let tile_map = layout.unwrap_or_else(|| Self::generate_tile_map(&options));
+        let window_size = Self::window_size(&windows);
+        let replay = playback
+            .is_none()
+            .then(|| Replay::new(options.clone(), tile_map.clone()));
+        Self::spawn_board(
+            &mut commands,
+            &options,
+            tile_map,
+            &board_assets,
+            window_size,
+            None,
+            replay,
+        );
+    }
+
+    /// System recomputing the board layout when the window is resized or its scale factor changes.
+    ///
+    /// The tiles are moved and resized in place, keeping the game state and the board options
+    fn relayout_board(
+        mut commands: Commands,
+        mut board: ResMut<Board>,
+        board_assets: Res<BoardAssets>,
+        windows: Res<Windows>,
+        mut resized_evr: EventReader<WindowResized>,
+        mut scale_factor_evr: EventReader<WindowScaleFactorChanged>,
+        themed: Query<(Entity, &Themed)>,
+        mut tiles: Query<(&Coordinates, &mut Transform)>,
+        mut transforms: Query<&mut Transform, Without<Coordinates>>,
+    ) {
+        let primary = WindowId::primary();
+        let resized = resized_evr.iter().any(|e| e.id == primary);
+        let rescaled = scale_factor_evr.iter().any(|e| e.id == primary);
+        if !resized && !rescaled {
+            return;
+        }
+        let window_size = Self::window_size(&windows);
+        let (width, height) = (board.tile_map.width(), board.tile_map.height());
+        let (tile_size, board_size, board_position) =
+            Self::board_layout(&board.options, window_size, (width, height));
+        log::info!("board relayout, size: {}", board_size);
+        board.tile_size = tile_size;
+        board.bounds = Bounds2 {
+            position: board_position.xy(),
+            size: board_size,
+        };
+
+        // A running shake would move the board back to its previous position
+        commands.entity(board.entity).remove::<Tween>();
+        if let Ok(mut transform) = transforms.get_mut(board.entity) {
+            transform.translation = board_position;
+        }
+        for (entity, themed) in themed.iter() {
+            if *themed != Themed::Board {
+                continue;
+            }
+            if let Ok(mut transform) = transforms.get_mut(entity) {
+                transform.translation = Vec3::new(board_size.x / 2., board_size.y / 2., 0.);
+            }
+        }
+        for (coordinates, mut transform) in tiles.iter_mut() {
+            transform.translation.x = (coordinates.x as f32 * tile_size) + (tile_size / 2.);
+            transform.translation.y = (coordinates.y as f32 * tile_size) + (tile_size / 2.);
+        }
+        systems::theme::restyle(&mut commands, &board, &board_assets, &themed);
+    }
 
+    /// Generates a random tile map matching the `options`
+    fn generate_tile_map(options: &BoardOptions) -> TileMap {
         let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1);
         tile_map.set_bombs(options.bomb_count);
         #[cfg(feature = "debug")]
         log::info!("{}", tile_map.console_output());
+        tile_map
+    }
 
-        // We define the size of our tiles in world space
-        let tile_size = match options.tile_size {
-            TileSize::Fixed(v) => v,
-            TileSize::Adaptive { min, max } => Self::adaptative_tile_size(
-                window,
-                (min, max),
-                (tile_map.width(), tile_map.height()),
-            ),
-        };
-
-        // We deduce the size of the complete board
-        let board_size = Vec2::new(
-            tile_map.width() as f32 * tile_size,
-            tile_map.height() as f32 * tile_size,
-        );
+    /// Spawns the board entities for `tile_map` and inserts the `Board` and `GameStatus` resources,
+    /// with the `replay` recording the board.
+    ///
+    /// The tiles are uncovered and marked as in the `saved_game`, if any
+    fn spawn_board(
+        commands: &mut Commands,
+        options: &BoardOptions,
+        tile_map: TileMap,
+        board_assets: &BoardAssets,
+        window_size: Vec2,
+        saved_game: Option<&SavedGame>,
+        replay: Option<Replay>,
+    ) {
+        let (tile_size, board_size, board_position) =
+            Self::board_layout(options, window_size, (tile_map.width(), tile_map.height()));
         log::info!("board size: {}", board_size);
 
-        // We define the board anchor position (bottom left)
-        let board_position = match options.position {
-            BoardPosition::Centered { offset } => {
-                Vec3::new(-(board_size.x / 2.), -(board_size.y / 2.), 0.) + offset
-            }
-            BoardPosition::Custom(p) => p,
-        };
+        // Boards without a recording, like played back ones, are not recorded
+        match replay {
+            Some(replay) => commands.insert_resource(replay),
+            None => commands.remove_resource::<Replay>(),
+        }
 
         let mut covered_tiles =
-            HashMap::with_capacity((tile_map.width() * tile_map.height()).into());
+            HashMap::with_capacity(tile_map.width() as usize * tile_map.height() as usize);
 
         let mut safe_start = None;
 
@@ -129,59 +405,137 @@ impl<T> BoardPlugin<T> {
             .insert(GlobalTransform::default())
             .with_children(|parent| {
                 // We spawn the board background sprite at the center of the board, since the sprite pivot is centered
-                parent
-                    .spawn_bundle(SpriteBundle {
-                        sprite: Sprite {
-                            color: board_assets.board_material.color,
-                            custom_size: Some(board_size),
-                            ..Default::default()
-                        },
-                        texture: board_assets.board_material.texture.clone(),
-                        transform: Transform::from_xyz(board_size.x / 2., board_size.y / 2., 0.),
-                        ..Default::default()
-                    })
-                    .insert(Name::new("Background"));
+                let mut background = parent.spawn();
+                board_assets.insert_themed(
+                    &mut background,
+                    Themed::Board,
+                    board_size,
+                    Transform::from_xyz(board_size.x / 2., board_size.y / 2., 0.),
+                );
+                background.insert(Name::new("Background"));
 
                 Self::spawn_tiles(
                     parent,
                     &tile_map,
                     tile_size,
                     options.tile_padding,
-                    &board_assets,
+                    board_assets,
                     &mut covered_tiles,
                     &mut safe_start,
+                    saved_game,
+                );
+
+                // The keyboard tile cursor starts hidden, at the center of the board
+                let coordinates = Coordinates {
+                    x: tile_map.width() / 2,
+                    y: tile_map.height() / 2,
+                };
+                let mut cursor = parent.spawn();
+                board_assets.insert_themed(
+                    &mut cursor,
+                    Themed::Cursor,
+                    Vec2::splat(tile_size),
+                    systems::cursor::cursor_transform(coordinates, tile_size),
                 );
+                cursor
+                    .insert(Name::new("Tile Cursor"))
+                    .insert(TileCursor { coordinates })
+                    .insert(Visibility { is_visible: false });
+
+                // The mouse hover highlight is shown over covered tiles only
+                let mut hover = parent.spawn();
+                board_assets.insert_themed(
+                    &mut hover,
+                    Themed::Hover,
+                    Vec2::splat(tile_size),
+                    Transform::default(),
+                );
+                hover
+                    .insert(Name::new("Hover Highlight"))
+                    .insert(HoverHighlight)
+                    .insert(Visibility { is_visible: false });
             })
             .id();
 
-        if options.safe_start {
+        // A resumed game already started
+        if options.safe_start && saved_game.is_none() {
             if let Some(entity) = safe_start {
-                commands.entity(entity).insert(Uncover);
+                commands.entity(entity).insert(Uncover { move_id: None });
             }
         }
 
         // We add the main resource of the game, the board
         commands.insert_resource(Board {
+            options: options.clone(),
             tile_map,
             bounds: Bounds2 {
                 position: board_position.xy(),
                 size: board_size,
             },
             tile_size,
+            tile_padding: options.tile_padding,
             covered_tiles,
-            marked_tiles: Vec::new(),
+            marked_tiles: saved_game.map_or_else(Vec::new, |s| s.marked_tiles.clone()),
+            exploded_tiles: saved_game.map_or_else(Vec::new, |s| s.exploded_tiles.clone()),
+            lives: match (saved_game, options.bomb_rule) {
+                (Some(s), _) => s.lives,
+                (None, BombRule::Fatal) => None,
+                (None, BombRule::Lives(lives)) => Some(lives),
+            },
             entity: board_entity,
+            history: saved_game.map_or_else(Default::default, |s| s.history.clone()),
         });
+        commands.insert_resource(GameStatus {
+            elapsed: saved_game.map_or(Duration::ZERO, |s| s.elapsed),
+            ..Default::default()
+        });
+    }
+
+    /// Logical size of the primary window
+    fn window_size(windows: &Windows) -> Vec2 {
+        windows
+            .get_primary()
+            .map_or(Vec2::ZERO, |w| Vec2::new(w.width(), w.height()))
+    }
+
+    /// Computes the tile size, the board size and the board anchor position (bottom left).
+    /// The board fits the window below the HUD
+    fn board_layout(
+        options: &BoardOptions,
+        window_size: Vec2,
+        (width, height): (u16, u16), // Tile map dimensions
+    ) -> (f32, Vec2, Vec3) {
+        let available_size = window_size - Vec2::new(0., HUD_HEIGHT);
+        // We define the size of our tiles in world space
+        let tile_size = match options.tile_size {
+            TileSize::Fixed(v) => v,
+            TileSize::Adaptive { min, max } => {
+                Self::adaptative_tile_size(available_size, (min, max), (width, height))
+            }
+        };
+
+        // We deduce the size of the complete board
+        let board_size = Vec2::new(width as f32 * tile_size, height as f32 * tile_size);
+
+        // We define the board anchor position (bottom left)
+        let board_position = match options.position {
+            // Centered in the window below the HUD
+            BoardPosition::Centered { offset } => {
+                Vec3::new(-(board_size.x / 2.), -(board_size.y + HUD_HEIGHT) / 2., 0.) + offset
+            }
+            BoardPosition::Custom(p) => p,
+        };
+        (tile_size, board_size, board_position)
     }
 
     /// Computes a tile size that matches the window according to the tile map size
     fn adaptative_tile_size(
-        window: Res<WindowDescriptor>,
+        window_size: Vec2,
         (min, max): (f32, f32),      // Tile size constraints
         (width, height): (u16, u16), // Tile map dimensions
     ) -> f32 {
-        let max_width = window.width / width as f32;
-        let max_heigth = window.height / height as f32;
+        let max_width = window_size.x / width as f32;
+        let max_heigth = window_size.y / height as f32;
         max_width.min(max_heigth).clamp(min, max)
     }
 
@@ -193,7 +547,16 @@ impl<T> BoardPlugin<T> {
         board_assets: &BoardAssets,
         covered_tiles: &mut HashMap<Coordinates, Entity>,
         safe_start_entity: &mut Option<Entity>,
+        saved_game: Option<&SavedGame>,
     ) {
+        let (uncovered, marked, exploded): (HashSet<_>, HashSet<_>, HashSet<_>) = match saved_game {
+            None => Default::default(),
+            Some(s) => (
+                s.uncovered_tiles.iter().copied().collect(),
+                s.marked_tiles.iter().copied().collect(),
+                s.exploded_tiles.iter().copied().collect(),
+            ),
+        };
         // Tiles
         for (y, line) in tile_map.iter().enumerate() {
             for (x, tile) in line.iter().enumerate() {
@@ -201,72 +564,69 @@ impl<T> BoardPlugin<T> {
                     x: x as u16,
                     y: y as u16,
                 };
+                let tile_size = Vec2::splat(size - padding);
                 let mut cmd = parent.spawn();
-                cmd.insert_bundle(SpriteBundle {
-                    sprite: Sprite {
-                        color: board_assets.tile_material.color,
-                        custom_size: Some(Vec2::splat(size - padding)),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_xyz(
+                board_assets.insert_themed(
+                    &mut cmd,
+                    Themed::Tile,
+                    tile_size,
+                    Transform::from_xyz(
                         (x as f32 * size) + (size / 2.),
                         (y as f32 * size) + (size / 2.),
                         1.,
                     ),
-                    texture: board_assets.tile_material.texture.clone(),
-                    ..Default::default()
-                })
-                .insert(Name::new(format!("Tile ({}, {})", x, y)))
-                .insert(coordinates);
-
-                // We add the cover sprites
-                cmd.with_children(|parent| {
-                    let entity = parent
-                        .spawn_bundle(SpriteBundle {
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(size - padding)),
-                                color: board_assets.covered_tile_material.color,
-                                ..Default::default()
-                            },
-                            texture: board_assets.covered_tile_material.texture.clone(),
-                            transform: Transform::from_xyz(0., 0., 2.),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Tile Cover"))
-                        .id();
-                    covered_tiles.insert(coordinates, entity);
-                    // TODO: should be a random tile instead of a first occurence of empty tile
-                    if safe_start_entity.is_none() && *tile == Tile::Empty {
-                        *safe_start_entity = Some(entity);
-                    }
-                });
+                );
+                cmd.insert(Name::new(format!("Tile ({}, {})", x, y)))
+                    .insert(coordinates);
+
+                // We add the cover sprites, with their flag
+                if !uncovered.contains(&coordinates) {
+                    cmd.with_children(|parent| {
+                        let mut cover = parent.spawn();
+                        board_assets.insert_covered_tile(&mut cover, tile_size);
+                        if marked.contains(&coordinates) {
+                            cover.with_children(|parent| {
+                                board_assets.insert_flag(&mut parent.spawn(), Vec2::splat(size));
+                            });
+                        }
+                        let entity = cover.id();
+                        covered_tiles.insert(coordinates, entity);
+                        // TODO: should be a random tile instead of a first occurence of empty tile
+                        if safe_start_entity.is_none() && *tile == Tile::Empty {
+                            *safe_start_entity = Some(entity);
+                        }
+                    });
+                }
+                // Bombs uncovered without ending the game keep their flag
+                if exploded.contains(&coordinates) {
+                    cmd.with_children(|parent| {
+                        board_assets.insert_exploded_flag(&mut parent.spawn(), Vec2::splat(size));
+                    });
+                }
 
                 match tile {
                     // If the tile is a bomb we add the matching component and a sprite child
                     Tile::Bomb => {
                         cmd.insert(Bomb);
                         cmd.with_children(|parent| {
-                            parent.spawn_bundle(SpriteBundle {
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::splat(size - padding)),
-                                    color: board_assets.bomb_material.color,
-                                    ..Default::default()
-                                },
-                                transform: Transform::from_xyz(0., 0., 1.),
-                                texture: board_assets.bomb_material.texture.clone(),
-                                ..Default::default()
-                            });
+                            board_assets.insert_themed(
+                                &mut parent.spawn(),
+                                Themed::Bomb,
+                                tile_size,
+                                Transform::from_xyz(0., 0., 1.),
+                            );
                         });
                     }
-                    // If the tile is a bomb neighbour we add the matching component and a text child
+                    // If the tile is a bomb neighbour we add the matching component and a counter child
                     Tile::BombNeighbor(v) => {
                         cmd.insert(BombNeighbor { count: *v });
                         cmd.with_children(|parent| {
-                            parent.spawn_bundle(Self::bomb_count_text_bundle(
-                                *v,
-                                board_assets,
-                                size - padding,
-                            ));
+                            board_assets.insert_themed(
+                                &mut parent.spawn(),
+                                Themed::BombCounter(*v),
+                                tile_size,
+                                Transform::from_xyz(0., 0., 1.),
+                            );
                         });
                     }
                     Tile::Empty => (),
@@ -274,28 +634,25 @@ impl<T> BoardPlugin<T> {
             }
         }
     }
+}
 
-    /// Generates the bomb counter text 2D Bundle for a given value
-    fn bomb_count_text_bundle(count: u8, board_assets: &BoardAssets, size: f32) -> Text2dBundle {
-        let color = board_assets.bomb_counter_color(count);
-        // generate a text bundle
-        Text2dBundle {
-            text: Text {
-                sections: vec![TextSection {
-                    value: count.to_string(),
-                    style: TextStyle {
-                        color,
-                        font: board_assets.bomb_counter_font.clone(),
-                        font_size: size,
-                    },
-                }],
-                alignment: TextAlignment {
-                    vertical: VerticalAlign::Center,
-                    horizontal: HorizontalAlign::Center,
-                },
-            },
-            transform: Transform::from_xyz(0., 0., 1.),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_fits_below_the_hud() {
+        let options = BoardOptions {
+            tile_size: TileSize::Adaptive { min: 1., max: 100. },
             ..Default::default()
-        }
+        };
+        let window_size = Vec2::new(400., 250.);
+        let (tile_size, board_size, position) =
+            BoardPlugin::<()>::board_layout(&options, window_size, (4, 4));
+        assert_eq!(tile_size, (250. - HUD_HEIGHT) / 4.);
+        assert_eq!(board_size, Vec2::splat(250. - HUD_HEIGHT));
+        // The board top touches the HUD, the window center is the origin
+        assert_eq!(position.y + board_size.y, window_size.y / 2. - HUD_HEIGHT);
+        assert_eq!(position.y, -window_size.y / 2.);
     }
 }
@@ -1,25 +1,39 @@
 use crate::bounds::Bounds2;
-use crate::{Coordinates, TileMap};
+use crate::resources::{BoardOptions, History};
+use crate::{BoardCamera, Coordinates, TileMap};
 use bevy::log;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
 #[derive(Debug)]
 pub struct Board {
+    /// Options the board was spawned with, a resumed game keeps its own instead of the player's
+    /// `BoardOptions`
+    pub options: BoardOptions,
     pub tile_map: TileMap,
     pub bounds: Bounds2,
     pub tile_size: f32,
+    pub tile_padding: f32,
     pub covered_tiles: HashMap<Coordinates, Entity>,
     pub marked_tiles: Vec<Coordinates>,
+    pub exploded_tiles: Vec<Coordinates>,
+    /// Remaining lives, `None` when any bomb is fatal
+    pub lives: Option<u8>,
     pub entity: Entity,
+    pub history: History,
 }
 
 impl Board {
-    /// Translates a mouse position to board coordinates
-    pub fn mouse_position(&self, window: &Window, position: Vec2) -> Option<Coordinates> {
+    /// Translates a mouse position to board coordinates through the board camera
+    pub fn mouse_position(
+        &self,
+        window: &Window,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        position: Vec2,
+    ) -> Option<Coordinates> {
         // Window to world space
-        let window_size = Vec2::new(window.width(), window.height());
-        let position = position - window_size / 2.;
+        let position = BoardCamera::cursor_to_world(window, camera, camera_transform, position);
 
         // Bounds check
         if !self.bounds.in_bounds(position) {
@@ -42,14 +56,37 @@ impl Board {
         }
     }
 
-    /// We try to uncover a tile, returning the entity
-    pub fn try_uncover_tile(&mut self, coords: &Coordinates) -> Option<Entity> {
+    /// We try to uncover a tile, returning the entity. The uncovering is recorded in the history
+    /// move `move_id`, if any
+    pub fn try_uncover_tile(
+        &mut self,
+        coords: &Coordinates,
+        move_id: Option<u64>,
+    ) -> Option<Entity> {
+        let was_marked = self.marked_tiles.contains(coords);
+        let entity = self.uncover_tile(coords)?;
+        if let Some(id) = move_id {
+            self.history.record_uncover(id, *coords, was_marked);
+        }
+        Some(entity)
+    }
+
+    /// Uncovers a tile without recording it in the history, returning the entity
+    pub fn uncover_tile(&mut self, coords: &Coordinates) -> Option<Entity> {
         if self.marked_tiles.contains(coords) {
             self.unmark_tile(coords)?;
         }
         self.covered_tiles.remove(coords)
     }
 
+    /// Registers a new cover `entity` for an uncovered tile, restoring its mark if needed
+    pub fn cover_tile(&mut self, coords: Coordinates, entity: Entity, marked: bool) {
+        self.covered_tiles.insert(coords, entity);
+        if marked && !self.marked_tiles.contains(&coords) {
+            self.marked_tiles.push(coords);
+        }
+    }
+
     /// We try to mark or unmark a tile, returning the entity and if the tile is marked
     pub fn try_toggle_mark(&mut self, coords: &Coordinates) -> Option<(Entity, bool)> {
         let entity = *self.covered_tiles.get(coords)?;
@@ -72,6 +109,27 @@ impl Board {
             .collect()
     }
 
+    /// Retrieves the covered tiles to uncover when chording on `coords`.
+    ///
+    /// Chording only applies to an uncovered bomb counter whose adjacent marked or exploded tiles
+    /// match its count, and uncovers its other adjacent covered tiles
+    pub fn chord_tiles(&self, coords: Coordinates) -> Vec<Coordinates> {
+        if self.covered_tiles.contains_key(&coords) || self.tile_map.is_bomb_at(coords) {
+            return Vec::new();
+        }
+        let count = self.tile_map.bomb_count_at(coords);
+        let flagged =
+            |c: &Coordinates| self.marked_tiles.contains(c) || self.exploded_tiles.contains(c);
+        let adjacent_flags = self.tile_map.safe_square_at(coords).filter(flagged).count();
+        if count == 0 || adjacent_flags != count as usize {
+            return Vec::new();
+        }
+        self.tile_map
+            .safe_square_at(coords)
+            .filter(|c| self.covered_tiles.contains_key(c) && !flagged(c))
+            .collect()
+    }
+
     /// Removes the `coords` from `marked_tiles`
     fn unmark_tile(&mut self, coords: &Coordinates) -> Option<Coordinates> {
         let pos = match self.marked_tiles.iter().position(|a| a == coords) {
@@ -84,8 +142,217 @@ impl Board {
         Some(self.marked_tiles.remove(pos))
     }
 
+    /// Registers an uncovered bomb, returning `true` if it ends the game
+    pub fn explode_bomb(&mut self, coords: Coordinates) -> bool {
+        self.exploded_tiles.push(coords);
+        match self.lives.as_mut() {
+            None => true,
+            Some(lives) => {
+                *lives = lives.saturating_sub(1);
+                *lives == 0
+            }
+        }
+    }
+
+    /// Cancels an uncovered bomb, giving back its life. Returns `false` if `coords` did not explode
+    pub fn restore_bomb(&mut self, coords: &Coordinates) -> bool {
+        let pos = match self.exploded_tiles.iter().position(|c| c == coords) {
+            None => return false,
+            Some(p) => p,
+        };
+        self.exploded_tiles.remove(pos);
+        if let Some(lives) = self.lives.as_mut() {
+            *lives += 1;
+        }
+        true
+    }
+
+    /// Bomb count minus the marked and exploded tiles, negative when too many tiles are marked
+    pub fn remaining_mines(&self) -> i32 {
+        self.tile_map.bomb_count() as i32
+            - self.marked_tiles.len() as i32
+            - self.exploded_tiles.len() as i32
+    }
+
+    /// Did an uncovered bomb end the game
+    pub fn is_lost(&self) -> bool {
+        match self.lives {
+            None => !self.exploded_tiles.is_empty(),
+            Some(lives) => lives == 0 && !self.exploded_tiles.is_empty(),
+        }
+    }
+
     /// Is the board complete
     pub fn is_completed(&self) -> bool {
-        self.tile_map.bomb_count() as usize == self.covered_tiles.len()
+        self.tile_map.bomb_count() as usize == self.covered_tiles.len() + self.exploded_tiles.len()
+    }
+}
+
+#[cfg(test)]
+impl Board {
+    /// Board of `tile_map` with every tile covered, without spawned entities
+    pub(crate) fn covered(tile_map: TileMap) -> Self {
+        let (width, height) = (tile_map.width(), tile_map.height());
+        let covered_tiles = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Coordinates { x, y }))
+            .enumerate()
+            .map(|(i, c)| (c, Entity::from_raw(i as u32)))
+            .collect();
+        Self {
+            options: BoardOptions {
+                map_size: (width, height),
+                bomb_count: tile_map.bomb_count(),
+                ..Default::default()
+            },
+            tile_map,
+            bounds: Bounds2 {
+                position: Vec2::ZERO,
+                size: Vec2::new(width as f32, height as f32),
+            },
+            tile_size: 1.,
+            tile_padding: 0.,
+            covered_tiles,
+            marked_tiles: Vec::new(),
+            exploded_tiles: Vec::new(),
+            lives: None,
+            entity: Entity::from_raw(u32::MAX),
+            history: History::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    /// 3x3 board with bombs at the bottom left and top right corners, center uncovered
+    fn board() -> Board {
+        let tile_map = TileMap::with_bombs(3, 3, [c(0, 0), c(2, 2)]).unwrap();
+        let mut board = Board::covered(tile_map);
+        board.uncover_tile(&c(1, 1));
+        board
+    }
+
+    fn sorted(mut tiles: Vec<Coordinates>) -> Vec<Coordinates> {
+        tiles.sort();
+        tiles
+    }
+
+    #[test]
+    fn chord_needs_matching_flags() {
+        let mut board = board();
+        assert!(board.chord_tiles(c(1, 1)).is_empty());
+        board.try_toggle_mark(&c(0, 0));
+        assert!(board.chord_tiles(c(1, 1)).is_empty());
+        board.try_toggle_mark(&c(2, 2));
+        assert_eq!(
+            sorted(board.chord_tiles(c(1, 1))),
+            vec![c(0, 1), c(0, 2), c(1, 0), c(1, 2), c(2, 0), c(2, 1)]
+        );
+    }
+
+    #[test]
+    fn chord_counts_exploded_bombs_as_flags() {
+        let mut board = board();
+        board.try_toggle_mark(&c(0, 0));
+        board.uncover_tile(&c(2, 2));
+        board.explode_bomb(c(2, 2));
+        assert_eq!(board.chord_tiles(c(1, 1)).len(), 6);
+    }
+
+    #[test]
+    fn chord_with_too_many_flags_does_nothing() {
+        let mut board = board();
+        board.try_toggle_mark(&c(0, 0));
+        board.try_toggle_mark(&c(2, 2));
+        board.try_toggle_mark(&c(1, 0));
+        assert!(board.chord_tiles(c(1, 1)).is_empty());
+    }
+
+    #[test]
+    fn chord_only_applies_to_uncovered_counters() {
+        let mut board = board();
+        board.try_toggle_mark(&c(0, 0));
+        board.try_toggle_mark(&c(2, 2));
+        // Covered counter
+        assert!(board.chord_tiles(c(1, 0)).is_empty());
+        // Bomb
+        board.uncover_tile(&c(0, 0));
+        assert!(board.chord_tiles(c(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn chord_of_an_empty_tile_does_nothing() {
+        let tile_map = TileMap::with_bombs(3, 3, [c(0, 0)]).unwrap();
+        let mut board = Board::covered(tile_map);
+        board.uncover_tile(&c(2, 2));
+        assert!(board.chord_tiles(c(2, 2)).is_empty());
+    }
+
+    /// 3x3 board with bombs on the bottom row corners and the top right corner, center uncovered
+    fn board_with_lives(lives: Option<u8>) -> Board {
+        let tile_map = TileMap::with_bombs(3, 3, [c(0, 0), c(2, 0), c(2, 2)]).unwrap();
+        let mut board = Board::covered(tile_map);
+        board.lives = lives;
+        board.uncover_tile(&c(1, 1));
+        board
+    }
+
+    /// Uncovers the bomb at `coords`, returning `true` if it ends the game
+    fn explode(board: &mut Board, coords: Coordinates) -> bool {
+        board.uncover_tile(&coords);
+        board.explode_bomb(coords)
+    }
+
+    #[test]
+    fn any_bomb_is_fatal_without_lives() {
+        let mut board = board_with_lives(None);
+        assert!(!board.is_lost());
+        assert!(explode(&mut board, c(0, 0)));
+        assert!(board.is_lost());
+    }
+
+    #[test]
+    fn lives_allow_as_many_explosions() {
+        let mut board = board_with_lives(Some(3));
+        assert!(!explode(&mut board, c(0, 0)));
+        assert!(!explode(&mut board, c(2, 0)));
+        assert_eq!(board.lives, Some(1));
+        assert!(!board.is_lost());
+        // The last life ends the game
+        assert!(explode(&mut board, c(2, 2)));
+        assert_eq!(board.lives, Some(0));
+        assert!(board.is_lost());
+    }
+
+    #[test]
+    fn exploded_bombs_count_as_found() {
+        let mut board = board_with_lives(Some(3));
+        explode(&mut board, c(0, 0));
+        assert_eq!(board.remaining_mines(), 2);
+        for coords in [c(1, 0), c(0, 1), c(2, 1), c(0, 2), c(1, 2)] {
+            board.uncover_tile(&coords);
+        }
+        // The two bombs left covered and the exploded one
+        assert!(board.is_completed());
+        assert!(!board.is_lost());
+    }
+
+    #[test]
+    fn undoing_an_explosion_gives_the_life_back() {
+        let mut board = board_with_lives(Some(1));
+        assert!(explode(&mut board, c(0, 0)));
+        assert!(board.is_lost());
+        assert!(board.restore_bomb(&c(0, 0)));
+        assert_eq!(board.lives, Some(1));
+        assert!(board.exploded_tiles.is_empty());
+        assert!(!board.is_lost());
+        // Only exploded bombs give a life back
+        assert!(!board.restore_bomb(&c(2, 0)));
+        assert_eq!(board.lives, Some(1));
     }
 }
@@ -1,5 +1,9 @@
+use crate::components::{ExplodedFlag, Themed};
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
+use bevy::text::Text2dSize;
+use serde::Deserialize;
 
 /// Material of a `Sprite` with a texture and color
 #[derive(Debug, Clone)]
@@ -17,6 +21,169 @@ impl Default for SpriteMaterial {
     }
 }
 
+/// Particle emission settings of an effect, distances are in tiles
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    /// Emitted particle count
+    pub count: u16,
+    /// Maximum particle lifetime, in seconds
+    pub lifetime: f32,
+    /// Maximum initial speed, in tiles per second
+    pub speed: f32,
+    /// Particle size, in tiles
+    pub size: f32,
+    /// Size multiplier reached at the end of the particle life
+    pub growth: f32,
+    /// Downward acceleration in tiles per second squared, particles rise if negative
+    pub gravity: f32,
+    /// Maximum rotation speed, in radians per second
+    pub spin: f32,
+    /// Particle colors, picked randomly
+    pub colors: Vec<Color>,
+}
+
+/// Particle effects of the board
+#[derive(Debug, Clone)]
+pub struct BoardParticles {
+    /// Debris thrown by an exploded bomb
+    pub debris: ParticleEmitter,
+    /// Smoke rising from an exploded bomb
+    pub smoke: ParticleEmitter,
+    /// Confetti thrown over the board on victory
+    pub confetti: ParticleEmitter,
+}
+
+impl Default for BoardParticles {
+    fn default() -> Self {
+        Self {
+            debris: ParticleEmitter {
+                count: 24,
+                lifetime: 0.8,
+                speed: 6.,
+                size: 0.15,
+                growth: 1.,
+                gravity: 15.,
+                spin: 10.,
+                colors: vec![Color::BLACK, Color::DARK_GRAY, Color::ORANGE_RED],
+            },
+            smoke: ParticleEmitter {
+                count: 12,
+                lifetime: 1.5,
+                speed: 1.,
+                size: 0.5,
+                growth: 2.5,
+                gravity: -1.5,
+                spin: 1.,
+                colors: vec![
+                    Color::rgba(0.3, 0.3, 0.3, 0.6),
+                    Color::rgba(0.5, 0.5, 0.5, 0.5),
+                ],
+            },
+            confetti: ParticleEmitter {
+                count: 150,
+                lifetime: 3.,
+                speed: 4.,
+                size: 0.2,
+                growth: 1.,
+                gravity: 3.,
+                spin: 8.,
+                colors: vec![
+                    Color::RED,
+                    Color::GREEN,
+                    Color::BLUE,
+                    Color::YELLOW,
+                    Color::PINK,
+                    Color::CYAN,
+                ],
+            },
+        }
+    }
+}
+
+/// Frame indices of the tile states in a `TileAtlas`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtlasFrames {
+    /// Covered tile
+    pub covered: usize,
+    /// Uncovered tile
+    pub revealed: usize,
+    /// Bomb counters, from 1 to 8
+    pub numbers: [usize; 8],
+    /// Flag
+    pub flag: usize,
+    /// Question mark. Themes must provide it, but tiles can only be flagged so it is not drawn yet
+    pub question: usize,
+    /// Bomb
+    pub bomb: usize,
+    /// Exploded bomb
+    pub exploded: usize,
+    /// Flag on a tile without bomb
+    pub wrong_flag: usize,
+}
+
+impl AtlasFrames {
+    /// Every frame index, to check them against the atlas size
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        [
+            self.covered,
+            self.revealed,
+            self.flag,
+            self.question,
+            self.bomb,
+            self.exploded,
+            self.wrong_flag,
+        ]
+        .into_iter()
+        .chain(self.numbers)
+    }
+}
+
+/// Tile skin drawing every tile state from the frames of one texture atlas
+#[derive(Debug, Clone)]
+pub struct TileAtlas {
+    /// Atlas texture
+    pub texture: Handle<Image>,
+    /// Texture atlas layout
+    pub atlas: Handle<TextureAtlas>,
+    /// Tile state frames
+    pub frames: AtlasFrames,
+    /// Sample the texture with nearest filtering, for pixel art
+    pub pixelated: bool,
+}
+
+/// Visual components of a `Themed` entity
+#[derive(Debug, Clone)]
+pub enum ThemedVisual {
+    /// Plain sprite
+    Sprite(Sprite, Handle<Image>),
+    /// Texture atlas frame
+    AtlasSprite(TextureAtlasSprite, Handle<TextureAtlas>),
+    /// 2D text
+    Text(Text),
+}
+
+impl ThemedVisual {
+    /// Inserts the visual components into `entity`
+    pub fn insert(self, entity: &mut EntityCommands) {
+        match self {
+            Self::Sprite(sprite, texture) => entity.insert(sprite).insert(texture),
+            Self::AtlasSprite(sprite, atlas) => entity.insert(sprite).insert(atlas),
+            Self::Text(text) => entity.insert(text).insert(Text2dSize::default()),
+        };
+    }
+
+    /// Removes every kind of visual component from `entity`
+    pub fn remove(entity: &mut EntityCommands) {
+        entity
+            .remove::<Sprite>()
+            .remove::<Handle<Image>>()
+            .remove::<TextureAtlasSprite>()
+            .remove::<Handle<TextureAtlas>>()
+            .remove::<Text>()
+            .remove::<Text2dSize>();
+    }
+}
+
 /// Assets for the board. Must be used as a resource.
 ///
 /// Use the loader for partial setup
@@ -24,20 +191,72 @@ impl Default for SpriteMaterial {
 pub struct BoardAssets {
     /// Label
     pub label: String,
-    ///
+    /// Board background material
     pub board_material: SpriteMaterial,
-    ///
+    /// Uncovered tile material
     pub tile_material: SpriteMaterial,
-    ///
+    /// Covered tile material
     pub covered_tile_material: SpriteMaterial,
-    ///
+    /// Bomb counter text font
     pub bomb_counter_font: Handle<Font>,
-    ///
+    /// Bomb counter text colors, indexed by counter value
     pub bomb_counter_colors: Vec<Color>,
-    ///
+    /// Flag sprite material
     pub flag_material: SpriteMaterial,
-    ///
+    /// Material of the flags of tiles without bomb, once the game is lost
+    pub wrong_flag_material: SpriteMaterial,
+    /// Bomb sprite material
     pub bomb_material: SpriteMaterial,
+    /// Keyboard tile cursor material
+    pub cursor_material: SpriteMaterial,
+    /// Mouse hover highlight material
+    pub hover_material: SpriteMaterial,
+    /// Texture atlas skin, replacing the tile materials and bomb counter texts
+    pub tile_atlas: Option<TileAtlas>,
+    /// Particle effects
+    pub particles: BoardParticles,
+}
+
+impl Default for BoardAssets {
+    /// Plain colors, used until a theme is loaded
+    fn default() -> Self {
+        Self {
+            label: "Default".to_string(),
+            board_material: SpriteMaterial::default(),
+            tile_material: SpriteMaterial {
+                color: Color::DARK_GRAY,
+                ..Default::default()
+            },
+            covered_tile_material: SpriteMaterial {
+                color: Color::GRAY,
+                ..Default::default()
+            },
+            bomb_counter_font: Default::default(),
+            bomb_counter_colors: Self::default_colors(),
+            flag_material: SpriteMaterial {
+                color: Color::ORANGE_RED,
+                ..Default::default()
+            },
+            wrong_flag_material: SpriteMaterial {
+                color: Color::GRAY,
+                ..Default::default()
+            },
+            bomb_material: SpriteMaterial {
+                color: Color::BLACK,
+                ..Default::default()
+            },
+            cursor_material: SpriteMaterial {
+                color: Color::rgba(1., 1., 0., 0.35),
+                ..Default::default()
+            },
+            hover_material: SpriteMaterial {
+                color: Color::rgba(1., 1., 1., 0.2),
+                ..Default::default()
+            },
+            tile_atlas: None,
+            particles: Default::default(),
+        }
+    }
 }
 
 impl BoardAssets {
@@ -63,4 +282,107 @@ impl BoardAssets {
             },
         }
     }
+
+    /// Generates the visual components of a `themed` entity for a given size
+    pub fn visual(&self, themed: Themed, size: Vec2) -> ThemedVisual {
+        if let Some(atlas) = &self.tile_atlas {
+            let frames = &atlas.frames;
+            let index = match themed {
+                Themed::Board | Themed::Cursor | Themed::Hover => None,
+                Themed::Tile | Themed::Pressed => Some(frames.revealed),
+                Themed::CoveredTile => Some(frames.covered),
+                Themed::Flag => Some(frames.flag),
+                Themed::WrongFlag => Some(frames.wrong_flag),
+                Themed::Bomb => Some(frames.bomb),
+                Themed::Exploded => Some(frames.exploded),
+                Themed::BombCounter(count) => {
+                    Some(frames.numbers[(count as usize).clamp(1, 8) - 1])
+                }
+            };
+            if let Some(index) = index {
+                let sprite = TextureAtlasSprite {
+                    index,
+                    custom_size: Some(size),
+                    ..Default::default()
+                };
+                return ThemedVisual::AtlasSprite(sprite, atlas.atlas.clone());
+            }
+        }
+        let material = match themed {
+            Themed::Board => &self.board_material,
+            Themed::Tile | Themed::Pressed => &self.tile_material,
+            Themed::CoveredTile => &self.covered_tile_material,
+            Themed::Flag | Themed::Exploded => &self.flag_material,
+            Themed::WrongFlag => &self.wrong_flag_material,
+            Themed::Bomb => &self.bomb_material,
+            Themed::Cursor => &self.cursor_material,
+            Themed::Hover => &self.hover_material,
+            Themed::BombCounter(count) => {
+                return ThemedVisual::Text(self.bomb_count_text(count, size.y))
+            }
+        };
+        let sprite = Sprite {
+            custom_size: Some(size),
+            color: material.color,
+            ..Default::default()
+        };
+        ThemedVisual::Sprite(sprite, material.texture.clone())
+    }
+
+    /// Inserts the components of a `themed` entity of a given size, drawn at `transform`
+    pub fn insert_themed(
+        &self,
+        entity: &mut EntityCommands,
+        themed: Themed,
+        size: Vec2,
+        transform: Transform,
+    ) {
+        entity
+            .insert(themed)
+            .insert(transform)
+            .insert(GlobalTransform::default())
+            .insert(Visibility::default());
+        self.visual(themed, size).insert(entity);
+    }
+
+    /// Inserts the components of a tile cover of a given size
+    pub fn insert_covered_tile(&self, entity: &mut EntityCommands, size: Vec2) {
+        let transform = Transform::from_xyz(0., 0., 2.);
+        self.insert_themed(entity, Themed::CoveredTile, size, transform);
+        entity.insert(Name::new("Tile Cover"));
+    }
+
+    /// Inserts the components of a flag of a given size
+    pub fn insert_flag(&self, entity: &mut EntityCommands, size: Vec2) {
+        let transform = Transform::from_xyz(0., 0., 1.);
+        self.insert_themed(entity, Themed::Flag, size, transform);
+        entity.insert(Name::new("Flag"));
+    }
+
+    /// Inserts the components of the flag of an exploded bomb of a given size, above the bomb
+    pub fn insert_exploded_flag(&self, entity: &mut EntityCommands, size: Vec2) {
+        let transform = Transform::from_xyz(0., 0., 2.);
+        self.insert_themed(entity, Themed::Exploded, size, transform);
+        entity
+            .insert(Name::new("Exploded Flag"))
+            .insert(ExplodedFlag);
+    }
+
+    /// Generates the bomb counter text for a given value
+    fn bomb_count_text(&self, count: u8, size: f32) -> Text {
+        Text {
+            sections: vec![TextSection {
+                value: count.to_string(),
+                style: TextStyle {
+                    color: self.bomb_counter_color(count),
+                    font: self.bomb_counter_font.clone(),
+                    font_size: size,
+                },
+            }],
+            alignment: TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        }
+    }
 }
@@ -19,9 +19,20 @@ pub enum BoardPosition {
     Custom(Vec3),
 }
 
+/// Rule applied when a bomb is uncovered
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+pub enum BombRule {
+    /// Any uncovered bomb ends the game
+    #[default]
+    Fatal,
+    /// Every uncovered bomb costs a life, the game ends when none remain
+    Lives(u8),
+}
+
 /// Board generation options. Must be used as a resource
 // We use serde to allow saving option presets and loading them at runtime
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BoardOptions {
     /// Tile map size
     pub map_size: (u16, u16),
@@ -35,6 +46,14 @@ pub struct BoardOptions {
     pub tile_padding: f32,
     /// Does the board generate a safe place to start
     pub safe_start: bool,
+    /// Practice mode allows undoing a move which uncovered a bomb
+    pub practice_mode: bool,
+    /// What happens when a bomb is uncovered
+    pub bomb_rule: BombRule,
+    /// Plays the reveal, flag and explosion animations and the particle effects
+    pub animations: bool,
+    /// Seconds a touch must be held to act as a long press
+    pub long_press_delay: f32,
 }
 
 impl Default for TileSize {
@@ -63,6 +82,10 @@ impl Default for BoardOptions {
             tile_size: Default::default(),
             tile_padding: 0.,
             safe_start: false,
+            practice_mode: false,
+            bomb_rule: Default::default(),
+            animations: true,
+            long_press_delay: 0.5,
         }
     }
-}
\ No newline at end of file
+}
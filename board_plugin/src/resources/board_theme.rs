@@ -0,0 +1,415 @@
+use crate::resources::{
+    AtlasFrames, BoardAssets, BoardParticles, ParticleEmitter, SpriteMaterial, TileAtlas,
+};
+use bevy::asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
+use serde::Deserialize;
+
+/// Board theme asset, loaded from `.theme.ron` files
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "6f1c7a52-2d0b-4c6e-9a43-0f3e5b7d9c21"]
+pub struct BoardTheme {
+    pub assets: BoardAssets,
+}
+
+/// Theme applied to the board. Must be used as a resource
+#[derive(Debug, Clone)]
+pub struct ActiveTheme(pub Handle<BoardTheme>);
+
+/// Sprite material as described in a theme file
+#[derive(Debug, Clone, Deserialize)]
+struct MaterialDescriptor {
+    /// Hexadecimal color, white by default
+    #[serde(default)]
+    color: Option<String>,
+    /// Texture asset path, a plain color is used by default
+    #[serde(default)]
+    texture: Option<String>,
+}
+
+/// Texture atlas skin as described in a theme file
+#[derive(Debug, Clone, Deserialize)]
+struct AtlasDescriptor {
+    /// Atlas texture asset path
+    texture: String,
+    /// Size of a frame in pixels
+    frame_size: (f32, f32),
+    columns: usize,
+    rows: usize,
+    /// Nearest filtering, for pixel art
+    #[serde(default)]
+    pixelated: bool,
+    frames: AtlasFrames,
+}
+
+/// Particle emitter as described in a theme file
+#[derive(Debug, Clone, Deserialize)]
+struct EmitterDescriptor {
+    count: u16,
+    lifetime: f32,
+    speed: f32,
+    size: f32,
+    #[serde(default = "default_growth")]
+    growth: f32,
+    #[serde(default)]
+    gravity: f32,
+    #[serde(default)]
+    spin: f32,
+    /// Hexadecimal colors
+    colors: Vec<String>,
+}
+
+/// Particle effects as described in a theme file
+#[derive(Debug, Clone, Deserialize)]
+struct ParticlesDescriptor {
+    debris: EmitterDescriptor,
+    smoke: EmitterDescriptor,
+    confetti: EmitterDescriptor,
+}
+
+/// Theme file content
+#[derive(Debug, Clone, Deserialize)]
+struct ThemeDescriptor {
+    label: String,
+    board: MaterialDescriptor,
+    tile: MaterialDescriptor,
+    covered_tile: MaterialDescriptor,
+    flag: MaterialDescriptor,
+    bomb: MaterialDescriptor,
+    bomb_counter_font: String,
+    bomb_counter_colors: Vec<String>,
+    /// Keyboard tile cursor, a translucent yellow is used if missing
+    #[serde(default)]
+    cursor: Option<MaterialDescriptor>,
+    /// Mouse hover highlight, a translucent white is used if missing
+    #[serde(default)]
+    hover: Option<MaterialDescriptor>,
+    /// Flag of a tile without bomb once the game is lost, a gray flag is used if missing
+    #[serde(default)]
+    wrong_flag: Option<MaterialDescriptor>,
+    /// Optional texture atlas skin
+    #[serde(default)]
+    atlas: Option<AtlasDescriptor>,
+    /// Particle effects, the default ones are used if missing
+    #[serde(default)]
+    particles: Option<ParticlesDescriptor>,
+}
+
+impl ThemeDescriptor {
+    /// Parses a theme file, checking its colors, atlas frames and particle emitters
+    fn parse(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let descriptor: Self = ron::de::from_bytes(bytes)?;
+        for hex in descriptor.colors() {
+            parse_color(hex)?;
+        }
+        if let Some(atlas) = &descriptor.atlas {
+            let frame_count = atlas.columns * atlas.rows;
+            if let Some(index) = atlas.frames.indices().find(|i| *i >= frame_count) {
+                anyhow::bail!("atlas frame {} out of the {} frames", index, frame_count);
+            }
+        }
+        if descriptor.emitters().any(|e| e.colors.is_empty()) {
+            anyhow::bail!("particle emitters need at least one color");
+        }
+        Ok(descriptor)
+    }
+
+    /// Particle emitters of the theme
+    fn emitters(&self) -> impl Iterator<Item = &EmitterDescriptor> {
+        self.particles
+            .iter()
+            .flat_map(|p| [&p.debris, &p.smoke, &p.confetti])
+    }
+
+    /// Every hexadecimal color of the theme
+    fn colors(&self) -> impl Iterator<Item = &str> {
+        let materials = [
+            Some(&self.board),
+            Some(&self.tile),
+            Some(&self.covered_tile),
+            Some(&self.flag),
+            Some(&self.bomb),
+            self.cursor.as_ref(),
+            self.hover.as_ref(),
+            self.wrong_flag.as_ref(),
+        ];
+        materials
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m.color.as_deref())
+            .chain(self.bomb_counter_colors.iter().map(String::as_str))
+            .chain(
+                self.emitters()
+                    .flat_map(|e| e.colors.iter().map(String::as_str)),
+            )
+    }
+}
+
+/// Loads `BoardTheme` assets from RON theme files
+#[derive(Debug, Default)]
+pub struct BoardThemeLoader;
+
+impl AssetLoader for BoardThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let descriptor = ThemeDescriptor::parse(bytes)?;
+            let mut dependencies = Vec::new();
+            let mut material = |m: &MaterialDescriptor| -> Result<SpriteMaterial, anyhow::Error> {
+                let color = match &m.color {
+                    None => Color::WHITE,
+                    Some(hex) => parse_color(hex)?,
+                };
+                let texture = match &m.texture {
+                    None => DEFAULT_IMAGE_HANDLE.typed(),
+                    Some(path) => {
+                        let path = AssetPath::from(path.as_str()).to_owned();
+                        dependencies.push(path.clone());
+                        load_context.get_handle(path)
+                    }
+                };
+                Ok(SpriteMaterial { color, texture })
+            };
+            let board_material = material(&descriptor.board)?;
+            let tile_material = material(&descriptor.tile)?;
+            let covered_tile_material = material(&descriptor.covered_tile)?;
+            let flag_material = material(&descriptor.flag)?;
+            let bomb_material = material(&descriptor.bomb)?;
+            let wrong_flag_material = match &descriptor.wrong_flag {
+                None => SpriteMaterial {
+                    color: Color::GRAY,
+                    texture: flag_material.texture.clone(),
+                },
+                Some(m) => material(m)?,
+            };
+            let cursor_material = match &descriptor.cursor {
+                None => BoardAssets::default().cursor_material,
+                Some(m) => material(m)?,
+            };
+            let hover_material = match &descriptor.hover {
+                None => BoardAssets::default().hover_material,
+                Some(m) => material(m)?,
+            };
+            let font_path = AssetPath::from(descriptor.bomb_counter_font.as_str()).to_owned();
+            dependencies.push(font_path.clone());
+            let bomb_counter_colors = descriptor
+                .bomb_counter_colors
+                .iter()
+                .map(|hex| parse_color(hex))
+                .collect::<Result<_, _>>()?;
+            let tile_atlas = match &descriptor.atlas {
+                None => None,
+                Some(atlas) => {
+                    let path = AssetPath::from(atlas.texture.as_str()).to_owned();
+                    dependencies.push(path.clone());
+                    let texture: Handle<Image> = load_context.get_handle(path);
+                    let layout = TextureAtlas::from_grid(
+                        texture.clone(),
+                        Vec2::new(atlas.frame_size.0, atlas.frame_size.1),
+                        atlas.columns,
+                        atlas.rows,
+                    );
+                    Some(TileAtlas {
+                        texture,
+                        atlas: load_context.set_labeled_asset("atlas", LoadedAsset::new(layout)),
+                        frames: atlas.frames.clone(),
+                        pixelated: atlas.pixelated,
+                    })
+                }
+            };
+            let particles = match &descriptor.particles {
+                None => BoardParticles::default(),
+                Some(particles) => BoardParticles {
+                    debris: emitter(&particles.debris)?,
+                    smoke: emitter(&particles.smoke)?,
+                    confetti: emitter(&particles.confetti)?,
+                },
+            };
+            let theme = BoardTheme {
+                assets: BoardAssets {
+                    label: descriptor.label,
+                    board_material,
+                    tile_material,
+                    covered_tile_material,
+                    bomb_counter_font: load_context.get_handle(font_path),
+                    bomb_counter_colors,
+                    flag_material,
+                    wrong_flag_material,
+                    bomb_material,
+                    cursor_material,
+                    hover_material,
+                    tile_atlas,
+                    particles,
+                },
+            };
+            load_context.set_default_asset(LoadedAsset::new(theme).with_dependencies(dependencies));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+fn default_growth() -> f32 {
+    1.
+}
+
+/// Converts a theme file particle emitter
+fn emitter(descriptor: &EmitterDescriptor) -> Result<ParticleEmitter, anyhow::Error> {
+    Ok(ParticleEmitter {
+        count: descriptor.count,
+        lifetime: descriptor.lifetime,
+        speed: descriptor.speed,
+        size: descriptor.size,
+        growth: descriptor.growth,
+        gravity: descriptor.gravity,
+        spin: descriptor.spin,
+        colors: descriptor
+            .colors
+            .iter()
+            .map(|hex| parse_color(hex))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Parses a `#RRGGBB` or `#RRGGBBAA` color, the `#` is optional
+fn parse_color(hex: &str) -> Result<Color, anyhow::Error> {
+    Color::hex(hex.trim_start_matches('#'))
+        .map_err(|e| anyhow::anyhow!("invalid color {:?}: {:?}", hex, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Theme with only the required fields
+    const MINIMAL_THEME: &str = r##"(
+        label: "Minimal",
+        board: (),
+        tile: (color: Some("#C0C0C0")),
+        covered_tile: (color: Some("808080")),
+        flag: (texture: Some("sprites/flag.png")),
+        bomb: (color: Some("#000000FF")),
+        bomb_counter_font: "fonts/pixeled.ttf",
+        bomb_counter_colors: ["#0000FF"],
+    )"##;
+
+    /// Minimal theme with the `atlas` and `particles` fields added
+    fn theme_with(fields: &str) -> String {
+        MINIMAL_THEME.replace("\n    )", &format!("\n{}\n    )", fields))
+    }
+
+    /// Atlas of `columns` x `rows` frames, using the frames 0 to 14
+    fn atlas(columns: usize, rows: usize) -> String {
+        format!(
+            r#"atlas: Some((
+                texture: "sprites/atlas.png",
+                frame_size: (16., 16.),
+                columns: {},
+                rows: {},
+                frames: (
+                    covered: 0,
+                    revealed: 1,
+                    numbers: (2, 3, 4, 5, 6, 7, 8, 9),
+                    flag: 10,
+                    question: 11,
+                    bomb: 12,
+                    exploded: 13,
+                    wrong_flag: 14,
+                ),
+            )),"#,
+            columns, rows
+        )
+    }
+
+    /// Particles whose confetti use the `confetti` colors
+    fn particles(confetti: &str) -> String {
+        format!(
+            r##"particles: Some((
+                debris: (count: 1, lifetime: 1., speed: 1., size: 1., colors: ["#000000"]),
+                smoke: (count: 1, lifetime: 1., speed: 1., size: 1., colors: ["#808080"]),
+                confetti: (count: 1, lifetime: 1., speed: 1., size: 1., colors: [{}]),
+            )),"##,
+            confetti
+        )
+    }
+
+    fn parse(theme: &str) -> Result<ThemeDescriptor, String> {
+        ThemeDescriptor::parse(theme.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn minimal_theme_is_parsed() {
+        let theme = parse(MINIMAL_THEME).unwrap();
+        assert_eq!(theme.label, "Minimal");
+        assert!(theme.board.color.is_none() && theme.board.texture.is_none());
+        assert_eq!(theme.flag.texture.as_deref(), Some("sprites/flag.png"));
+        assert!(theme.cursor.is_none() && theme.hover.is_none() && theme.wrong_flag.is_none());
+        assert!(theme.atlas.is_none() && theme.particles.is_none());
+        assert_eq!(theme.colors().count(), 4);
+    }
+
+    #[test]
+    fn shipped_themes_are_parsed() {
+        for theme in [
+            include_str!("../../../assets/themes/default.theme.ron"),
+            include_str!("../../../assets/themes/classic.theme.ron"),
+            include_str!("../../../assets/themes/night.theme.ron"),
+        ] {
+            assert!(parse(theme).is_ok(), "{:?}", parse(theme).err());
+        }
+    }
+
+    #[test]
+    fn atlas_frames_must_be_in_the_atlas() {
+        let theme = parse(&theme_with(&atlas(8, 2))).unwrap();
+        let descriptor = theme.atlas.unwrap();
+        assert_eq!(descriptor.frames.indices().max(), Some(14));
+        assert!(!descriptor.pixelated);
+
+        let error = parse(&theme_with(&atlas(7, 2))).unwrap_err();
+        assert_eq!(error, "atlas frame 14 out of the 14 frames");
+        let error = parse(&theme_with(&atlas(8, 1))).unwrap_err();
+        assert!(error.ends_with("out of the 8 frames"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_colors_are_rejected() {
+        let error = parse(&MINIMAL_THEME.replace("#C0C0C0", "#C0C0C")).unwrap_err();
+        assert!(error.starts_with("invalid color \"#C0C0C\""), "{}", error);
+        let error = parse(&MINIMAL_THEME.replace("#0000FF", "blue")).unwrap_err();
+        assert!(error.starts_with("invalid color \"blue\""), "{}", error);
+        let error = parse(&theme_with(&particles(r##""#FF0000", "#GG0000""##))).unwrap_err();
+        assert!(error.starts_with("invalid color \"#GG0000\""), "{}", error);
+        // Optional materials are checked too
+        let cursor = r#"cursor: Some((color: Some("yellow"))),"#;
+        assert!(parse(&theme_with(cursor)).is_err());
+    }
+
+    #[test]
+    fn particle_emitters_need_colors() {
+        assert!(parse(&theme_with(&particles(r##""#FF0000""##))).is_ok());
+        let error = parse(&theme_with(&particles(""))).unwrap_err();
+        assert_eq!(error, "particle emitters need at least one color");
+    }
+
+    #[test]
+    fn colors_are_hexadecimal() {
+        assert_eq!(parse_color("#FF0000").unwrap(), Color::RED);
+        assert_eq!(parse_color("0000FF").unwrap(), Color::BLUE);
+        assert_eq!(
+            parse_color("#FFFFFF80").unwrap(),
+            Color::rgba(1., 1., 1., 128. / 255.)
+        );
+        for invalid in ["", "#", "#FF000", "#FF00000", "#XYZXYZ", "red"] {
+            assert!(parse_color(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::resources::BoardOptions;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// Board difficulty presets, convertible into `BoardOptions`
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// 9x9 board with 10 bombs
+    Beginner,
+    /// 16x16 board with 40 bombs
+    #[default]
+    Intermediate,
+    /// 30x16 board with 99 bombs
+    Expert,
+    /// Player defined board
+    Custom {
+        width: u16,
+        height: u16,
+        bomb_count: u16,
+    },
+}
+
+/// Invalid custom difficulty
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DifficultyError {
+    /// Width out of the `Difficulty::MIN_SIZE..=Difficulty::MAX_SIZE` range
+    InvalidWidth(u16),
+    /// Height out of the `Difficulty::MIN_SIZE..=Difficulty::MAX_SIZE` range
+    InvalidHeight(u16),
+    /// Bomb count out of the `1..=max` range
+    InvalidBombCount { bomb_count: u16, max: u16 },
+}
+
+impl Difficulty {
+    /// Minimum width and height of a custom board
+    pub const MIN_SIZE: u16 = 5;
+    /// Maximum width and height of a custom board
+    pub const MAX_SIZE: u16 = 100;
+
+    /// Tile map size
+    pub const fn map_size(&self) -> (u16, u16) {
+        match self {
+            Self::Beginner => (9, 9),
+            Self::Intermediate => (16, 16),
+            Self::Expert => (30, 16),
+            Self::Custom { width, height, .. } => (*width, *height),
+        }
+    }
+
+    /// Bomb count
+    pub const fn bomb_count(&self) -> u16 {
+        match self {
+            Self::Beginner => 10,
+            Self::Intermediate => 40,
+            Self::Expert => 99,
+            Self::Custom { bomb_count, .. } => *bomb_count,
+        }
+    }
+
+    /// Maximum bomb count of a board, keeping room for a safe 3x3 start
+    pub fn max_bomb_count(width: u16, height: u16) -> u16 {
+        let tiles = width as u32 * height as u32;
+        tiles.saturating_sub(9).clamp(1, u16::MAX as u32) as u16
+    }
+
+    /// Checks the board size and bomb count constraints
+    pub fn validate(&self) -> Result<(), DifficultyError> {
+        let (width, height) = self.map_size();
+        let size_range = Self::MIN_SIZE..=Self::MAX_SIZE;
+        if !size_range.contains(&width) {
+            return Err(DifficultyError::InvalidWidth(width));
+        }
+        if !size_range.contains(&height) {
+            return Err(DifficultyError::InvalidHeight(height));
+        }
+        let bomb_count = self.bomb_count();
+        let max = Self::max_bomb_count(width, height);
+        if !(1..=max).contains(&bomb_count) {
+            return Err(DifficultyError::InvalidBombCount { bomb_count, max });
+        }
+        Ok(())
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Beginner => write!(f, "Beginner"),
+            Self::Intermediate => write!(f, "Intermediate"),
+            Self::Expert => write!(f, "Expert"),
+            Self::Custom {
+                width,
+                height,
+                bomb_count,
+            } => write!(f, "Custom {}x{}/{}", width, height, bomb_count),
+        }
+    }
+}
+
+impl Display for DifficultyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidWidth(w) => write!(
+                f,
+                "width {} must be between {} and {}",
+                w,
+                Difficulty::MIN_SIZE,
+                Difficulty::MAX_SIZE
+            ),
+            Self::InvalidHeight(h) => write!(
+                f,
+                "height {} must be between {} and {}",
+                h,
+                Difficulty::MIN_SIZE,
+                Difficulty::MAX_SIZE
+            ),
+            Self::InvalidBombCount { bomb_count, max } => {
+                write!(f, "mine count {} must be between 1 and {}", bomb_count, max)
+            }
+        }
+    }
+}
+
+impl Error for DifficultyError {}
+
+impl From<Difficulty> for BoardOptions {
+    fn from(difficulty: Difficulty) -> Self {
+        Self {
+            map_size: difficulty.map_size(),
+            bomb_count: difficulty.bomb_count(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&BoardOptions> for Difficulty {
+    /// Preset matching the board size and bomb count, or a custom difficulty
+    fn from(options: &BoardOptions) -> Self {
+        [Self::Beginner, Self::Intermediate, Self::Expert]
+            .into_iter()
+            .find(|d| d.map_size() == options.map_size && d.bomb_count() == options.bomb_count)
+            .unwrap_or(Self::Custom {
+                width: options.map_size.0,
+                height: options.map_size.1,
+                bomb_count: options.bomb_count,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(width: u16, height: u16, bomb_count: u16) -> Difficulty {
+        Difficulty::Custom {
+            width,
+            height,
+            bomb_count,
+        }
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for difficulty in [
+            Difficulty::Beginner,
+            Difficulty::Intermediate,
+            Difficulty::Expert,
+        ] {
+            assert_eq!(difficulty.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn size_must_be_within_bounds() {
+        assert_eq!(custom(5, 100, 1).validate(), Ok(()));
+        assert_eq!(custom(100, 5, 1).validate(), Ok(()));
+        assert_eq!(
+            custom(4, 10, 1).validate(),
+            Err(DifficultyError::InvalidWidth(4))
+        );
+        assert_eq!(
+            custom(101, 10, 1).validate(),
+            Err(DifficultyError::InvalidWidth(101))
+        );
+        assert_eq!(
+            custom(10, 4, 1).validate(),
+            Err(DifficultyError::InvalidHeight(4))
+        );
+        assert_eq!(
+            custom(10, 101, 1).validate(),
+            Err(DifficultyError::InvalidHeight(101))
+        );
+    }
+
+    #[test]
+    fn bomb_count_keeps_room_for_a_safe_start() {
+        assert_eq!(Difficulty::max_bomb_count(5, 5), 16);
+        assert_eq!(Difficulty::max_bomb_count(100, 100), 9991);
+        assert_eq!(custom(5, 5, 16).validate(), Ok(()));
+        assert_eq!(
+            custom(5, 5, 17).validate(),
+            Err(DifficultyError::InvalidBombCount {
+                bomb_count: 17,
+                max: 16
+            })
+        );
+        assert_eq!(
+            custom(5, 5, 0).validate(),
+            Err(DifficultyError::InvalidBombCount {
+                bomb_count: 0,
+                max: 16
+            })
+        );
+    }
+
+    #[test]
+    fn options_convert_back_to_their_difficulty() {
+        for difficulty in [
+            Difficulty::Beginner,
+            Difficulty::Intermediate,
+            Difficulty::Expert,
+            custom(20, 10, 30),
+        ] {
+            let options = BoardOptions::from(difficulty);
+            assert_eq!(options.map_size, difficulty.map_size());
+            assert_eq!(options.bomb_count, difficulty.bomb_count());
+            assert_eq!(Difficulty::from(&options), difficulty);
+        }
+    }
+
+    #[test]
+    fn custom_board_matching_a_preset_is_the_preset() {
+        let options = BoardOptions::from(custom(30, 16, 99));
+        assert_eq!(Difficulty::from(&options), Difficulty::Expert);
+        // The bomb count must match too
+        let options = BoardOptions::from(custom(30, 16, 98));
+        assert_eq!(Difficulty::from(&options), custom(30, 16, 98));
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+/// Live values of the current game, displayed by the HUD. Must be used as a resource
+#[derive(Debug, Clone, Default)]
+pub struct GameStatus {
+    /// Time spent playing since the first reveal
+    pub elapsed: Duration,
+    /// Board bomb count minus the marked tiles, negative when too many tiles are marked
+    pub remaining_mines: i32,
+    /// Remaining lives, `None` when any bomb is fatal
+    pub lives: Option<u8>,
+    /// Has the first tile been revealed
+    pub started: bool,
+    /// Is the game over, either completed or exploded
+    pub finished: bool,
+}
+
+impl GameStatus {
+    /// Is the timer currently counting
+    pub fn timer_running(&self) -> bool {
+        self.started && !self.finished
+    }
+}
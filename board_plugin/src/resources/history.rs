@@ -0,0 +1,266 @@
+use crate::components::Coordinates;
+use serde::{Deserialize, Serialize};
+
+/// Player action at the origin of a move
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MoveAction {
+    /// A tile was triggered for uncovering
+    Reveal(Coordinates),
+    /// A tile mark was toggled
+    ToggleMark(Coordinates),
+    /// The neighbors of an uncovered bomb counter were triggered for uncovering together
+    Chord(Coordinates),
+}
+
+/// A single player move and its effects on the board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Move {
+    /// Unique id of the move in its history, an undone cascade must not reach a newer move
+    #[serde(default)]
+    pub id: u64,
+    /// Action which started the move
+    pub action: MoveAction,
+    /// Every tile uncovered by the move, cascade included
+    pub uncovered: Vec<Coordinates>,
+    /// Marked tiles which lost their mark while being uncovered
+    pub unmarked: Vec<Coordinates>,
+    /// Did the move uncover a bomb
+    pub exploded: bool,
+}
+
+impl Move {
+    pub fn new(id: u64, action: MoveAction) -> Self {
+        Self {
+            id,
+            action,
+            uncovered: Vec::new(),
+            unmarked: Vec::new(),
+            exploded: false,
+        }
+    }
+}
+
+/// Undo/redo move history of a board
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    done: Vec<Move>,
+    undone: Vec<Move>,
+    undo_count: u32,
+    /// Id of the next move
+    #[serde(default)]
+    next_id: u64,
+}
+
+impl History {
+    /// Registers a new move, dropping the redo stack
+    pub fn push(&mut self, action: MoveAction) {
+        self.undone.clear();
+        // Histories saved without ids have every move at 0
+        let id = self
+            .done
+            .iter()
+            .map(|m| m.id + 1)
+            .fold(self.next_id, u64::max);
+        self.next_id = id + 1;
+        self.done.push(Move::new(id, action));
+    }
+
+    /// Id of the last played move, which the tiles it uncovers are recorded with
+    pub fn current_id(&self) -> Option<u64> {
+        self.done.last().map(|m| m.id)
+    }
+
+    /// Is the move `id` still played. A cascade of an undone move must stop
+    pub fn is_played(&self, id: u64) -> bool {
+        self.played(id).is_some()
+    }
+
+    /// Records a tile uncovered by the played move `id`.
+    ///
+    /// A cascade can span several frames, so its tiles are recorded in the move which started it
+    /// even if other moves were played since
+    pub fn record_uncover(&mut self, id: u64, coords: Coordinates, was_marked: bool) {
+        if let Some(m) = self.played_mut(id) {
+            m.uncovered.push(coords);
+            if was_marked {
+                m.unmarked.push(coords);
+            }
+        }
+    }
+
+    /// Records that the played move `id` uncovered a bomb
+    pub fn record_explosion(&mut self, id: u64) {
+        if let Some(m) = self.played_mut(id) {
+            m.exploded = true;
+        }
+    }
+
+    /// Played move `id`, cascades mostly belong to the last moves
+    fn played(&self, id: u64) -> Option<&Move> {
+        self.done.iter().rev().find(|m| m.id == id)
+    }
+
+    fn played_mut(&mut self, id: u64) -> Option<&mut Move> {
+        self.done.iter_mut().rev().find(|m| m.id == id)
+    }
+
+    /// Last played move
+    pub fn last(&self) -> Option<&Move> {
+        self.done.last()
+    }
+
+    /// Pops the last played move for undoing
+    pub fn undo(&mut self) -> Option<Move> {
+        let m = self.done.pop()?;
+        self.undo_count += 1;
+        Some(m)
+    }
+
+    /// Stores an undone move so it can be redone
+    pub fn push_undone(&mut self, m: Move) {
+        self.undone.push(m);
+    }
+
+    /// Pops the last undone move for redoing
+    pub fn redo(&mut self) -> Option<Move> {
+        self.undone.pop()
+    }
+
+    /// Stores a redone move back in the played moves
+    pub fn push_redone(&mut self, m: Move) {
+        self.done.push(m);
+    }
+
+    /// Played moves, oldest first
+    pub fn moves(&self) -> &[Move] {
+        &self.done
+    }
+
+    /// Coordinates of the played and undone moves
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.done.iter().chain(self.undone.iter()).flat_map(|m| {
+            let origin = match m.action {
+                MoveAction::Reveal(c) | MoveAction::ToggleMark(c) | MoveAction::Chord(c) => c,
+            };
+            std::iter::once(origin)
+                .chain(m.uncovered.iter().copied())
+                .chain(m.unmarked.iter().copied())
+        })
+    }
+
+    /// How many times undo was used. A game with undos should be excluded from records
+    pub fn undo_count(&self) -> u32 {
+        self.undo_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: Coordinates = Coordinates { x: 0, y: 0 };
+    const B: Coordinates = Coordinates { x: 1, y: 0 };
+    const C: Coordinates = Coordinates { x: 2, y: 0 };
+
+    #[test]
+    fn push_drops_the_redo_stack() {
+        let mut history = History::default();
+        history.push(MoveAction::Reveal(A));
+        let m = history.undo().unwrap();
+        history.push_undone(m);
+        history.push(MoveAction::ToggleMark(B));
+        assert!(history.redo().is_none());
+        assert_eq!(history.moves().len(), 1);
+        assert_eq!(history.last().unwrap().action, MoveAction::ToggleMark(B));
+    }
+
+    #[test]
+    fn undo_and_redo_move_between_stacks() {
+        let mut history = History::default();
+        history.push(MoveAction::Reveal(A));
+        history.push(MoveAction::ToggleMark(B));
+        let m = history.undo().unwrap();
+        assert_eq!(m.action, MoveAction::ToggleMark(B));
+        history.push_undone(m);
+        assert_eq!(history.undo_count(), 1);
+        assert_eq!(history.current_id(), Some(0));
+
+        let m = history.redo().unwrap();
+        assert_eq!(m.action, MoveAction::ToggleMark(B));
+        history.push_redone(m);
+        assert_eq!(history.moves().len(), 2);
+        assert!(history.redo().is_none());
+        // Redoing is not undoing
+        assert_eq!(history.undo_count(), 1);
+    }
+
+    #[test]
+    fn undo_of_an_empty_history_does_nothing() {
+        let mut history = History::default();
+        assert!(history.undo().is_none());
+        assert_eq!(history.undo_count(), 0);
+        assert_eq!(history.current_id(), None);
+    }
+
+    #[test]
+    fn uncovered_tiles_are_recorded_in_their_move() {
+        let mut history = History::default();
+        history.push(MoveAction::Reveal(A));
+        // A flag played while the reveal cascade is still running
+        history.push(MoveAction::ToggleMark(C));
+        history.record_uncover(0, A, false);
+        history.record_uncover(0, B, true);
+        history.record_explosion(0);
+
+        let reveal = &history.moves()[0];
+        assert_eq!(reveal.uncovered, vec![A, B]);
+        assert_eq!(reveal.unmarked, vec![B]);
+        assert!(reveal.exploded);
+        let flag = &history.moves()[1];
+        assert!(flag.uncovered.is_empty());
+        assert!(!flag.exploded);
+    }
+
+    #[test]
+    fn tiles_of_an_undone_move_are_not_recorded() {
+        let mut history = History::default();
+        history.push(MoveAction::Reveal(A));
+        history.record_uncover(0, A, false);
+        let m = history.undo().unwrap();
+        history.push_undone(m);
+        assert!(!history.is_played(0));
+        history.record_uncover(0, B, false);
+        let m = history.redo().unwrap();
+        assert_eq!(m.uncovered, vec![A]);
+    }
+
+    #[test]
+    fn undone_cascade_does_not_reach_a_new_move() {
+        let mut history = History::default();
+        history.push(MoveAction::Reveal(A));
+        let cascade = history.current_id().unwrap();
+        let m = history.undo().unwrap();
+        history.push_undone(m);
+        // The new move takes the place of the undone one
+        history.push(MoveAction::Reveal(B));
+        assert_ne!(history.current_id(), Some(cascade));
+        assert!(!history.is_played(cascade));
+        history.record_uncover(cascade, C, false);
+        history.record_explosion(cascade);
+        let m = history.last().unwrap();
+        assert!(m.uncovered.is_empty());
+        assert!(!m.exploded);
+    }
+
+    #[test]
+    fn moves_saved_without_ids_get_new_ids() {
+        let saved = concat!(
+            "(done: [(action: Reveal((x: 0, y: 0)), uncovered: [], unmarked: [], exploded: false)],",
+            " undone: [], undo_count: 0)"
+        );
+        let mut history: History = ron::from_str(saved).unwrap();
+        history.push(MoveAction::ToggleMark(B));
+        assert_eq!(history.current_id(), Some(1));
+        assert!(history.is_played(0));
+    }
+}
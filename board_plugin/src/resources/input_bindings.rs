@@ -0,0 +1,489 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+
+/// Game action which can be bound to inputs
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    /// Reveals the pointed tile
+    Reveal,
+    /// Flags or unflags the pointed tile
+    Flag,
+    /// Reveals the unflagged neighbors of the pointed tile
+    Chord,
+    /// Tile cursor moves
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    /// Held to move the cursor to the board edge or to the previous covered tile
+    CursorJump,
+    /// Moves the cursor to the next covered tile
+    NextCoveredTile,
+    /// Camera moves
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    /// Held to pan the camera by moving the mouse
+    DragCamera,
+    /// Toggles the pause
+    Pause,
+    /// Starts a game, or a new board during a game
+    NewGame,
+    /// Starts a game of a difficulty preset
+    BeginnerGame,
+    IntermediateGame,
+    ExpertGame,
+    /// Opens the custom game dialog
+    CustomGame,
+    /// Quits the current game
+    ClearGame,
+    /// Restarts the current board with the same layout
+    RestartBoard,
+    Undo,
+    Redo,
+}
+
+impl InputAction {
+    /// Every bindable action, in display order
+    pub const ALL: [Self; 24] = [
+        Self::Reveal,
+        Self::Flag,
+        Self::Chord,
+        Self::CursorLeft,
+        Self::CursorRight,
+        Self::CursorUp,
+        Self::CursorDown,
+        Self::CursorJump,
+        Self::NextCoveredTile,
+        Self::PanLeft,
+        Self::PanRight,
+        Self::PanUp,
+        Self::PanDown,
+        Self::DragCamera,
+        Self::Pause,
+        Self::NewGame,
+        Self::BeginnerGame,
+        Self::IntermediateGame,
+        Self::ExpertGame,
+        Self::CustomGame,
+        Self::ClearGame,
+        Self::RestartBoard,
+        Self::Undo,
+        Self::Redo,
+    ];
+}
+
+impl Display for InputAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Reveal => "Reveal",
+            Self::Flag => "Flag",
+            Self::Chord => "Chord",
+            Self::CursorLeft => "Cursor left",
+            Self::CursorRight => "Cursor right",
+            Self::CursorUp => "Cursor up",
+            Self::CursorDown => "Cursor down",
+            Self::CursorJump => "Cursor jump",
+            Self::NextCoveredTile => "Next covered",
+            Self::PanLeft => "Pan left",
+            Self::PanRight => "Pan right",
+            Self::PanUp => "Pan up",
+            Self::PanDown => "Pan down",
+            Self::DragCamera => "Drag camera",
+            Self::Pause => "Pause",
+            Self::NewGame => "New game",
+            Self::BeginnerGame => "Beginner game",
+            Self::IntermediateGame => "Intermediate game",
+            Self::ExpertGame => "Expert game",
+            Self::CustomGame => "Custom game",
+            Self::ClearGame => "Clear game",
+            Self::RestartBoard => "Restart",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Input triggering an action
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum InputBinding {
+    Mouse(MouseButton),
+    /// Key pressed without holding control, unless it is a control key itself
+    Key(KeyCode),
+    /// Key pressed while holding control
+    CtrlKey(KeyCode),
+    /// Button of any gamepad
+    Gamepad(GamepadButtonType),
+}
+
+/// Device of an input binding
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InputDevice {
+    Mouse,
+    Keyboard,
+    Gamepad,
+}
+
+impl InputBinding {
+    /// Device the binding is read from, a remapped action keeps one binding per device
+    pub fn device(&self) -> InputDevice {
+        match self {
+            Self::Mouse(_) => InputDevice::Mouse,
+            Self::Key(_) | Self::CtrlKey(_) => InputDevice::Keyboard,
+            Self::Gamepad(_) => InputDevice::Gamepad,
+        }
+    }
+}
+
+impl Display for InputBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mouse(MouseButton::Left) => write!(f, "LMB"),
+            Self::Mouse(MouseButton::Right) => write!(f, "RMB"),
+            Self::Mouse(MouseButton::Middle) => write!(f, "MMB"),
+            Self::Mouse(MouseButton::Other(b)) => write!(f, "Mouse{}", b),
+            Self::Key(key) => write!(f, "{:?}", key),
+            Self::CtrlKey(key) => write!(f, "Ctrl+{:?}", key),
+            Self::Gamepad(button) => write!(f, "Pad {:?}", button),
+        }
+    }
+}
+
+/// Inputs bound to each game action. Must be used as a resource
+// We use serde to save the bindings in the settings file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SavedBindings")]
+pub struct InputBindings {
+    bindings: BTreeMap<InputAction, Vec<InputBinding>>,
+}
+
+/// Input bindings read from the settings file, which may miss the actions added since
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SavedBindings {
+    bindings: BTreeMap<InputAction, Vec<InputBinding>>,
+}
+
+impl From<SavedBindings> for InputBindings {
+    fn from(saved: SavedBindings) -> Self {
+        let mut result = Self {
+            bindings: saved.bindings,
+        };
+        for (action, inputs) in Self::right_handed().bindings {
+            if result.bindings.contains_key(&action) {
+                continue;
+            }
+            // The missing actions get their default inputs the player did not bind elsewhere
+            let free: Vec<_> = inputs
+                .into_iter()
+                .filter(|b| result.actions(*b).next().is_none())
+                .collect();
+            result.bindings.insert(action, free);
+        }
+        result
+    }
+}
+
+impl InputBindings {
+    /// Right handed mouse, arrows or `hjkl` cursor and WASD camera
+    pub fn right_handed() -> Self {
+        use InputAction::*;
+        use InputBinding::*;
+        Self::from_bindings([
+            (Reveal, vec![Mouse(MouseButton::Left), Key(KeyCode::Space)]),
+            (Reveal, vec![Gamepad(GamepadButtonType::South)]),
+            (Flag, vec![Mouse(MouseButton::Right), Key(KeyCode::F)]),
+            (Flag, vec![Gamepad(GamepadButtonType::East)]),
+            (Chord, vec![Key(KeyCode::Return)]),
+            (Chord, vec![Gamepad(GamepadButtonType::West)]),
+            (CursorLeft, vec![Key(KeyCode::Left), Key(KeyCode::H)]),
+            (CursorLeft, vec![Gamepad(GamepadButtonType::DPadLeft)]),
+            (CursorRight, vec![Key(KeyCode::Right), Key(KeyCode::L)]),
+            (CursorRight, vec![Gamepad(GamepadButtonType::DPadRight)]),
+            (CursorUp, vec![Key(KeyCode::Up), Key(KeyCode::K)]),
+            (CursorUp, vec![Gamepad(GamepadButtonType::DPadUp)]),
+            (CursorDown, vec![Key(KeyCode::Down), Key(KeyCode::J)]),
+            (CursorDown, vec![Gamepad(GamepadButtonType::DPadDown)]),
+            (CursorJump, vec![Key(KeyCode::LShift), Key(KeyCode::RShift)]),
+            (NextCoveredTile, vec![Key(KeyCode::Tab)]),
+            (PanLeft, vec![Key(KeyCode::A)]),
+            (PanRight, vec![Key(KeyCode::D)]),
+            (PanUp, vec![Key(KeyCode::W)]),
+            (PanDown, vec![Key(KeyCode::S)]),
+            (DragCamera, vec![Mouse(MouseButton::Middle)]),
+            (Pause, vec![Key(KeyCode::Escape)]),
+            (Pause, vec![Gamepad(GamepadButtonType::Start)]),
+            (NewGame, vec![Key(KeyCode::G)]),
+            (BeginnerGame, vec![Key(KeyCode::Key1)]),
+            (IntermediateGame, vec![Key(KeyCode::Key2)]),
+            (ExpertGame, vec![Key(KeyCode::Key3)]),
+            (CustomGame, vec![Key(KeyCode::Key4)]),
+            (ClearGame, vec![Key(KeyCode::C)]),
+            (RestartBoard, vec![Key(KeyCode::R)]),
+            (Undo, vec![CtrlKey(KeyCode::Z)]),
+            (Redo, vec![CtrlKey(KeyCode::Y)]),
+        ])
+    }
+
+    /// Swapped mouse buttons, with the camera on the numeric keypad next to the right hand
+    pub fn left_handed() -> Self {
+        use InputAction::*;
+        use InputBinding::*;
+        let mut bindings = Self::right_handed();
+        for (action, binding) in [
+            (Reveal, Mouse(MouseButton::Right)),
+            (Flag, Mouse(MouseButton::Left)),
+            (PanLeft, Key(KeyCode::Numpad4)),
+            (PanRight, Key(KeyCode::Numpad6)),
+            (PanUp, Key(KeyCode::Numpad8)),
+            (PanDown, Key(KeyCode::Numpad2)),
+        ] {
+            bindings.rebind(action, binding);
+        }
+        bindings
+    }
+
+    fn from_bindings(bindings: impl IntoIterator<Item = (InputAction, Vec<InputBinding>)>) -> Self {
+        let mut result = Self {
+            bindings: BTreeMap::new(),
+        };
+        for (action, inputs) in bindings {
+            result.bindings.entry(action).or_default().extend(inputs);
+        }
+        result
+    }
+
+    /// Inputs bound to `action`
+    pub fn get(&self, action: InputAction) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Actions bound to `binding`
+    pub fn actions(&self, binding: InputBinding) -> impl Iterator<Item = InputAction> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, inputs)| inputs.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// Binds `binding` to `action` only, replacing the other inputs of the action on the same device.
+    /// Returns the other actions `binding` was taken from
+    pub fn rebind(&mut self, action: InputAction, binding: InputBinding) -> Vec<InputAction> {
+        let mut conflicts = Vec::new();
+        for (other, inputs) in self.bindings.iter_mut() {
+            if *other != action && inputs.contains(&binding) {
+                conflicts.push(*other);
+            }
+            inputs.retain(|b| *b != binding);
+        }
+        let inputs = self.bindings.entry(action).or_default();
+        inputs.retain(|b| b.device() != binding.device());
+        inputs.push(binding);
+        conflicts
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self::right_handed()
+    }
+}
+
+/// Keys held for the `InputBinding::CtrlKey` bindings
+const CTRL_KEYS: [KeyCode; 2] = [KeyCode::LControl, KeyCode::RControl];
+
+/// Input resources read through the `InputBindings`
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    bindings: Res<'w, InputBindings>,
+    keys: Res<'w, Input<KeyCode>>,
+    mouse_buttons: Res<'w, Input<MouseButton>>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> ActionInput<'w, 's> {
+    /// The input bindings
+    pub fn bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    /// Is an input of `action` held
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.any_binding(action, None, |b| self.binding_pressed(b))
+    }
+
+    /// Was an input of `action` pressed this frame
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.any_binding(action, None, |b| self.binding_just_pressed(b))
+    }
+
+    /// Is an input of `action` on `device` held
+    pub fn pressed_on(&self, action: InputAction, device: InputDevice) -> bool {
+        self.any_binding(action, Some(device), |b| self.binding_pressed(b))
+    }
+
+    /// Was an input of `action` on `device` pressed this frame
+    pub fn just_pressed_on(&self, action: InputAction, device: InputDevice) -> bool {
+        self.any_binding(action, Some(device), |b| self.binding_just_pressed(b))
+    }
+
+    fn any_binding(
+        &self,
+        action: InputAction,
+        device: Option<InputDevice>,
+        f: impl Fn(InputBinding) -> bool,
+    ) -> bool {
+        self.bindings
+            .get(action)
+            .iter()
+            .filter(|b| device.is_none_or(|d| b.device() == d))
+            .any(|b| f(*b))
+    }
+
+    fn binding_pressed(&self, binding: InputBinding) -> bool {
+        match binding {
+            InputBinding::Mouse(button) => self.mouse_buttons.pressed(button),
+            InputBinding::Key(key) => self.plain_key(key) && self.keys.pressed(key),
+            InputBinding::CtrlKey(key) => self.ctrl() && self.keys.pressed(key),
+            InputBinding::Gamepad(button) => {
+                self.gamepad_buttons.get_pressed().any(|b| b.1 == button)
+            }
+        }
+    }
+
+    fn binding_just_pressed(&self, binding: InputBinding) -> bool {
+        match binding {
+            InputBinding::Mouse(button) => self.mouse_buttons.just_pressed(button),
+            InputBinding::Key(key) => self.plain_key(key) && self.keys.just_pressed(key),
+            InputBinding::CtrlKey(key) => self.ctrl() && self.keys.just_pressed(key),
+            InputBinding::Gamepad(button) => self
+                .gamepad_buttons
+                .get_just_pressed()
+                .any(|b| b.1 == button),
+        }
+    }
+
+    fn ctrl(&self) -> bool {
+        self.keys.any_pressed(CTRL_KEYS)
+    }
+
+    /// Can `key` trigger its plain key bindings, which are left to the `CtrlKey` ones while
+    /// control is held
+    fn plain_key(&self, key: KeyCode) -> bool {
+        CTRL_KEYS.contains(&key) || !self.ctrl()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+    use InputAction::*;
+    use InputBinding::{CtrlKey, Key, Mouse};
+
+    #[test]
+    fn rebind_returns_the_conflicting_actions() {
+        let mut bindings = InputBindings::right_handed();
+        assert_eq!(bindings.rebind(Flag, Key(KeyCode::Space)), vec![Reveal]);
+        assert_eq!(
+            bindings.get(Reveal),
+            [
+                Mouse(MouseButton::Left),
+                InputBinding::Gamepad(GamepadButtonType::South)
+            ]
+        );
+        assert_eq!(
+            bindings.get(Flag),
+            [
+                Mouse(MouseButton::Right),
+                InputBinding::Gamepad(GamepadButtonType::East),
+                Key(KeyCode::Space)
+            ]
+        );
+        // Binding an input again to its action is no conflict
+        assert!(bindings.rebind(Flag, Key(KeyCode::Space)).is_empty());
+    }
+
+    #[test]
+    fn saved_bindings_keep_the_defaults_of_missing_actions() {
+        let saved = "(bindings: {Reveal: [Key(R)], Undo: [Key(U)]})";
+        let bindings: InputBindings = ron::from_str(saved).unwrap();
+        assert_eq!(bindings.get(Reveal), [Key(KeyCode::R)]);
+        assert_eq!(bindings.get(Undo), [Key(KeyCode::U)]);
+        assert_eq!(bindings.get(Flag), InputBindings::right_handed().get(Flag));
+        // The default input of a missing action is left to the action the player bound it to
+        assert!(bindings.get(RestartBoard).is_empty());
+        assert_eq!(bindings.get(Redo), [CtrlKey(KeyCode::Y)]);
+    }
+
+    #[test]
+    fn saved_bindings_round_trip() {
+        let bindings = InputBindings::left_handed();
+        let saved = ron::to_string(&bindings).unwrap();
+        assert_eq!(ron::from_str::<InputBindings>(&saved).unwrap(), bindings);
+        assert_eq!(
+            ron::from_str::<InputBindings>("()").unwrap(),
+            InputBindings::right_handed()
+        );
+    }
+
+    #[test]
+    fn difficulties_have_default_keys() {
+        let bindings = InputBindings::right_handed();
+        for (key, action) in [
+            (KeyCode::Key1, BeginnerGame),
+            (KeyCode::Key2, IntermediateGame),
+            (KeyCode::Key3, ExpertGame),
+            (KeyCode::Key4, CustomGame),
+        ] {
+            assert_eq!(bindings.actions(Key(key)).collect::<Vec<_>>(), [action]);
+        }
+        // Settings saved before the difficulty actions get their default keys
+        let bindings: InputBindings = ron::from_str("(bindings: {Reveal: [Key(R)]})").unwrap();
+        assert_eq!(bindings.get(ExpertGame), [Key(KeyCode::Key3)]);
+    }
+
+    /// World holding the input resources, with `keys` held since this frame
+    fn world_with_keys(bindings: InputBindings, keys: &[KeyCode]) -> World {
+        let mut world = World::new();
+        let mut input = Input::<KeyCode>::default();
+        for key in keys {
+            input.press(*key);
+        }
+        world.insert_resource(bindings);
+        world.insert_resource(input);
+        world.insert_resource(Input::<MouseButton>::default());
+        world.insert_resource(Input::<GamepadButton>::default());
+        world
+    }
+
+    #[test]
+    fn plain_keys_are_ignored_while_holding_control() {
+        let mut world = world_with_keys(InputBindings::right_handed(), &[KeyCode::S]);
+        let mut state = SystemState::<ActionInput>::new(&mut world);
+        let input = state.get_mut(&mut world);
+        assert!(input.just_pressed(PanDown) && input.pressed(PanDown));
+
+        let keys = [KeyCode::LControl, KeyCode::S, KeyCode::Z];
+        let mut world = world_with_keys(InputBindings::right_handed(), &keys);
+        let mut state = SystemState::<ActionInput>::new(&mut world);
+        let input = state.get_mut(&mut world);
+        assert!(!input.just_pressed(PanDown) && !input.pressed(PanDown));
+        assert!(input.just_pressed(Undo));
+
+        // Control itself may still be bound as a plain key
+        let mut bindings = InputBindings::right_handed();
+        bindings.rebind(CursorJump, Key(KeyCode::RControl));
+        let mut world = world_with_keys(bindings, &[KeyCode::RControl]);
+        let mut state = SystemState::<ActionInput>::new(&mut world);
+        assert!(state.get_mut(&mut world).pressed(CursorJump));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::components::Coordinates;
+use crate::resources::tile_map::TileMap;
+use std::path::Path;
+
+/// Bomb of an ASCII layout
+const ASCII_BOMB: char = '*';
+/// Safe tile of an ASCII layout
+const ASCII_SAFE: char = '.';
+/// Size of the MBF header: width, height and big endian bomb count
+const MBF_HEADER_LEN: usize = 4;
+
+/// Bomb layout the next board is spawned with, instead of a random one.
+/// Must be used as a resource, it is removed once the board is spawned
+#[derive(Debug, Clone)]
+pub struct BoardLayout(pub TileMap);
+
+/// Bomb layout file formats.
+///
+/// Both formats list the rows from the top of the board, which is the last row of a `TileMap`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LayoutFormat {
+    /// Text grid, one line per row with `*` for bombs and `.` for safe tiles
+    Ascii,
+    /// Minesweeper Arbiter binary layout: width and height bytes, the big endian bomb count
+    /// on two bytes, then the x and y bytes of each bomb
+    Mbf,
+}
+
+impl LayoutFormat {
+    pub const ALL: [Self; 2] = [Self::Ascii, Self::Mbf];
+
+    /// File extension of the format
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Ascii => "txt",
+            Self::Mbf => "mbf",
+        }
+    }
+
+    /// Format matching the extension of `path`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL.into_iter().find(|f| f.extension() == extension)
+    }
+
+    /// Parses the layout file `content`
+    pub fn read(&self, content: &[u8]) -> Result<TileMap, String> {
+        match self {
+            Self::Ascii => {
+                let text = std::str::from_utf8(content).map_err(|e| e.to_string())?;
+                TileMap::from_ascii(text)
+            }
+            Self::Mbf => TileMap::from_mbf(content),
+        }
+    }
+
+    /// Writes `tile_map` as a layout file content
+    pub fn write(&self, tile_map: &TileMap) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Ascii => Ok(tile_map.to_ascii().into_bytes()),
+            Self::Mbf => tile_map.to_mbf(),
+        }
+    }
+}
+
+impl TileMap {
+    /// Parses an ASCII layout. Blank lines are ignored
+    pub fn from_ascii(text: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |r| r.chars().count());
+        if width == 0 || width > u16::MAX as usize || rows.len() > u16::MAX as usize {
+            return Err(format!("invalid layout size {}x{}", width, rows.len()));
+        }
+        let height = rows.len() as u16;
+        let mut bombs = Vec::new();
+        for (row, line) in rows.iter().enumerate() {
+            if line.chars().count() != width {
+                return Err(format!("row {} is not {} tiles wide", row + 1, width));
+            }
+            let y = height - 1 - row as u16;
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    ASCII_BOMB => bombs.push(Coordinates { x: x as u16, y }),
+                    ASCII_SAFE => (),
+                    _ => return Err(format!("unexpected {:?} in row {}", c, row + 1)),
+                }
+            }
+        }
+        Self::with_bombs(width as u16, height, bombs)
+    }
+
+    /// ASCII layout of the map
+    pub fn to_ascii(&self) -> String {
+        self.iter()
+            .rev()
+            .map(|row| {
+                let mut line: String = row
+                    .iter()
+                    .map(|tile| match tile.is_bomb() {
+                        true => ASCII_BOMB,
+                        false => ASCII_SAFE,
+                    })
+                    .collect();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+
+    /// Parses a Minesweeper Arbiter MBF layout
+    pub fn from_mbf(content: &[u8]) -> Result<Self, String> {
+        let (header, data) = match content.len() >= MBF_HEADER_LEN {
+            true => content.split_at(MBF_HEADER_LEN),
+            false => return Err("truncated MBF header".to_string()),
+        };
+        let (width, height) = (header[0] as u16, header[1] as u16);
+        let bomb_count = u16::from_be_bytes([header[2], header[3]]) as usize;
+        if width == 0 || height == 0 {
+            return Err(format!("invalid layout size {}x{}", width, height));
+        }
+        if data.len() != bomb_count * 2 {
+            return Err(format!(
+                "{} bytes of bombs for {} bombs",
+                data.len(),
+                bomb_count
+            ));
+        }
+        let mut bombs = Vec::with_capacity(bomb_count);
+        for bomb in data.chunks_exact(2) {
+            let (x, y) = (bomb[0] as u16, bomb[1] as u16);
+            if x >= width || y >= height {
+                return Err(format!("bomb ({}, {}) is out of the map", x, y));
+            }
+            bombs.push(Coordinates {
+                x,
+                y: height - 1 - y,
+            });
+        }
+        Self::with_bombs(width, height, bombs)
+    }
+
+    /// Minesweeper Arbiter MBF layout of the map, which is limited to 255x255 tiles
+    pub fn to_mbf(&self) -> Result<Vec<u8>, String> {
+        let (width, height) = match (u8::try_from(self.width()), u8::try_from(self.height())) {
+            (Ok(w), Ok(h)) => (w, h),
+            _ => {
+                return Err(format!(
+                    "a {}x{} map is too large for MBF",
+                    self.width(),
+                    self.height()
+                ))
+            }
+        };
+        let mut content = Vec::with_capacity(MBF_HEADER_LEN + self.bomb_count() as usize * 2);
+        content.extend([width, height]);
+        content.extend(self.bomb_count().to_be_bytes());
+        for coordinates in self.bombs() {
+            content.extend([coordinates.x as u8, height - 1 - coordinates.y as u8]);
+        }
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let text = "*..\n..*\n";
+        let tile_map = TileMap::from_ascii(text).unwrap();
+        assert_eq!((tile_map.width(), tile_map.height()), (3, 2));
+        // The first line is the top row
+        assert_eq!(tile_map.bombs().collect::<Vec<_>>(), [c(2, 0), c(0, 1)]);
+        assert_eq!(tile_map.to_ascii(), text);
+    }
+
+    #[test]
+    fn ascii_rejects_ragged_rows_and_unknown_tiles() {
+        assert!(TileMap::from_ascii("*..\n.*\n").is_err());
+        assert!(TileMap::from_ascii("*.x\n").is_err());
+        assert!(TileMap::from_ascii("\n\n").is_err());
+    }
+
+    #[test]
+    fn mbf_rows_are_listed_from_the_top() {
+        // 3x2 map, 2 bombs: top left and bottom right
+        let content = [3, 2, 0, 2, 0, 0, 2, 1];
+        let tile_map = TileMap::from_mbf(&content).unwrap();
+        assert_eq!((tile_map.width(), tile_map.height()), (3, 2));
+        assert_eq!(tile_map.bombs().collect::<Vec<_>>(), [c(2, 0), c(0, 1)]);
+        // Bombs are written from the bottom row
+        assert_eq!(tile_map.to_mbf().unwrap(), [3, 2, 0, 2, 2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn mbf_bomb_count_is_big_endian() {
+        let bombs = (0..300).map(|i| c(i % 20, i / 20));
+        let tile_map = TileMap::with_bombs(20, 20, bombs).unwrap();
+        let content = tile_map.to_mbf().unwrap();
+        assert_eq!(content[..MBF_HEADER_LEN], [20, 20, 1, 44]);
+        assert_eq!(content.len(), MBF_HEADER_LEN + 600);
+        let read = TileMap::from_mbf(&content).unwrap();
+        assert_eq!(read.bomb_count(), 300);
+        assert!(read.bombs().eq(tile_map.bombs()));
+    }
+
+    #[test]
+    fn mbf_rejects_a_truncated_header() {
+        assert!(TileMap::from_mbf(&[]).is_err());
+        assert!(TileMap::from_mbf(&[3, 2, 0]).is_err());
+    }
+
+    #[test]
+    fn mbf_rejects_a_bomb_count_mismatch() {
+        assert!(TileMap::from_mbf(&[3, 2, 0, 2, 0, 0]).is_err());
+        assert!(TileMap::from_mbf(&[3, 2, 0, 1, 0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn mbf_rejects_duplicate_bombs() {
+        assert!(TileMap::from_mbf(&[3, 2, 0, 2, 1, 1, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn mbf_rejects_bombs_out_of_the_map() {
+        assert!(TileMap::from_mbf(&[3, 2, 0, 1, 3, 0]).is_err());
+        assert!(TileMap::from_mbf(&[3, 2, 0, 1, 0, 2]).is_err());
+    }
+
+    #[test]
+    fn mbf_is_limited_to_255_tiles() {
+        assert!(TileMap::empty(255, 255).to_mbf().is_ok());
+        assert!(TileMap::empty(256, 1).to_mbf().is_err());
+        assert!(TileMap::empty(1, 256).to_mbf().is_err());
+    }
+}
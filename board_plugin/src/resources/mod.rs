@@ -1,9 +1,32 @@
 mod board;
 mod board_assets;
 mod board_options;
+mod board_theme;
+mod difficulty;
+mod game_status;
+mod history;
+mod input_bindings;
+mod layout;
+mod playback;
+mod replay;
+mod saved_game;
+mod tap_mode;
 pub(crate) mod tile;
 pub(crate) mod tile_map;
+mod tile_pointer;
 
 pub use board::*;
 pub use board_assets::*;
 pub use board_options::*;
+pub use board_theme::*;
+pub use difficulty::*;
+pub use game_status::*;
+pub use history::*;
+pub use input_bindings::*;
+pub use layout::*;
+pub use playback::*;
+pub use replay::*;
+pub use saved_game::*;
+pub use tap_mode::*;
+pub use tile_map::TileMap;
+pub use tile_pointer::*;
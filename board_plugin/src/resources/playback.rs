@@ -0,0 +1,210 @@
+use crate::components::Coordinates;
+use crate::resources::{Replay, ReplayAction, ReplayEvent};
+use std::time::Duration;
+
+/// Replay played back on the board in the playback state. Must be used as a resource
+#[derive(Debug, Clone)]
+pub struct Playback {
+    pub replay: Replay,
+    /// Playback speed factor
+    pub speed: f32,
+    pub paused: bool,
+    /// Position in the replay
+    time: Duration,
+    /// Index of the next replay event to apply
+    next_event: usize,
+    /// Has the board to be rebuilt before applying the events again
+    rebuild: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            speed: 1.,
+            paused: false,
+            time: Duration::ZERO,
+            next_event: 0,
+            rebuild: false,
+        }
+    }
+
+    /// Position in the replay
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Is the replay played up to its end
+    pub fn is_finished(&self) -> bool {
+        self.time >= self.replay.duration && self.next_event >= self.replay.events.len()
+    }
+
+    /// Advances the playback by `delta` real time, unless paused
+    pub fn tick(&mut self, delta: Duration) {
+        if !self.paused {
+            self.time = (self.time + delta.mul_f32(self.speed)).min(self.replay.duration);
+        }
+    }
+
+    /// Moves the playback to `time`, seeking backwards rebuilds the board
+    pub fn seek(&mut self, time: Duration) {
+        let time = time.min(self.replay.duration);
+        if time < self.time {
+            self.rebuild = true;
+            self.next_event = 0;
+        }
+        self.time = time;
+    }
+
+    /// Takes the board rebuild request of a backwards seek
+    pub fn take_rebuild(&mut self) -> bool {
+        std::mem::take(&mut self.rebuild)
+    }
+
+    /// Next replay event due at the current time, which is then considered applied
+    pub fn next_due_event(&mut self) -> Option<ReplayEvent> {
+        let event = *self.replay.events.get(self.next_event)?;
+        if event.time > self.time {
+            return None;
+        }
+        self.next_event += 1;
+        Some(event)
+    }
+
+    /// Peeks the action of the next replay event due at the current time
+    pub fn peek_due_action(&self) -> Option<ReplayAction> {
+        self.replay
+            .events
+            .get(self.next_event)
+            .filter(|e| e.time <= self.time)
+            .map(|e| e.action)
+    }
+
+    /// Tiles pointed by the player up to the applied events, oldest first
+    pub fn cursor_path(&self) -> impl DoubleEndedIterator<Item = Coordinates> + '_ {
+        self.replay.events[..self.next_event]
+            .iter()
+            .filter_map(|e| match e.action {
+                ReplayAction::Cursor(coordinates) => coordinates,
+                _ => None,
+            })
+    }
+
+    /// Time of the first reveal, when the game timer started
+    pub fn start_time(&self) -> Option<Duration> {
+        self.replay
+            .events
+            .iter()
+            .find(|e| matches!(e.action, ReplayAction::Reveal(_) | ReplayAction::Chord(_)))
+            .map(|e| e.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{BoardOptions, TileMap};
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    fn secs(secs: f32) -> Duration {
+        Duration::from_secs_f32(secs)
+    }
+
+    /// 10 seconds replay of a 3x3 board: the cursor moves, then a reveal at 2s and a flag at 5s
+    fn replay() -> Replay {
+        let tile_map = TileMap::with_bombs(3, 3, [c(0, 0)]).unwrap();
+        let mut replay = Replay::new(BoardOptions::default(), tile_map);
+        for (time, action) in [
+            (0., ReplayAction::Cursor(Some(c(2, 2)))),
+            (2., ReplayAction::Reveal(c(2, 2))),
+            (4., ReplayAction::Cursor(Some(c(0, 0)))),
+            (5., ReplayAction::Flag(c(0, 0))),
+        ] {
+            replay.duration = secs(time);
+            replay.record(action);
+        }
+        replay.duration = secs(10.);
+        replay
+    }
+
+    /// Actions of the events due at the current time
+    fn due_actions(playback: &mut Playback) -> Vec<ReplayAction> {
+        std::iter::from_fn(|| playback.next_due_event())
+            .map(|e| e.action)
+            .collect()
+    }
+
+    #[test]
+    fn events_are_due_at_their_time() {
+        let mut playback = Playback::new(replay());
+        assert_eq!(playback.start_time(), Some(secs(2.)));
+        assert_eq!(
+            due_actions(&mut playback),
+            [ReplayAction::Cursor(Some(c(2, 2)))]
+        );
+        playback.tick(secs(3.));
+        assert_eq!(
+            playback.peek_due_action(),
+            Some(ReplayAction::Reveal(c(2, 2)))
+        );
+        assert_eq!(due_actions(&mut playback), [ReplayAction::Reveal(c(2, 2))]);
+        assert!(due_actions(&mut playback).is_empty());
+        assert_eq!(playback.cursor_path().collect::<Vec<_>>(), [c(2, 2)]);
+    }
+
+    #[test]
+    fn tick_is_scaled_by_the_speed_and_stops_when_paused() {
+        let mut playback = Playback::new(replay());
+        playback.speed = 2.;
+        playback.tick(secs(1.5));
+        assert_eq!(playback.time(), secs(3.));
+        playback.paused = true;
+        playback.tick(secs(1.));
+        assert_eq!(playback.time(), secs(3.));
+    }
+
+    #[test]
+    fn time_is_clamped_to_the_duration() {
+        let mut playback = Playback::new(replay());
+        playback.speed = 4.;
+        playback.tick(secs(5.));
+        assert_eq!(playback.time(), secs(10.));
+        assert!(!playback.is_finished());
+        assert_eq!(due_actions(&mut playback).len(), 4);
+        assert!(playback.is_finished());
+        playback.seek(secs(20.));
+        assert_eq!(playback.time(), secs(10.));
+    }
+
+    #[test]
+    fn seeking_forward_keeps_the_board() {
+        let mut playback = Playback::new(replay());
+        due_actions(&mut playback);
+        playback.seek(secs(4.5));
+        assert!(!playback.take_rebuild());
+        assert_eq!(due_actions(&mut playback).len(), 2);
+    }
+
+    #[test]
+    fn seeking_backwards_rebuilds_the_board() {
+        let mut playback = Playback::new(replay());
+        playback.seek(secs(6.));
+        assert_eq!(due_actions(&mut playback).len(), 4);
+        playback.seek(secs(3.));
+        assert!(playback.take_rebuild());
+        // The rebuild request is only taken once
+        assert!(!playback.take_rebuild());
+        assert_eq!(playback.cursor_path().count(), 0);
+        // The events are applied again from the start
+        assert_eq!(
+            due_actions(&mut playback),
+            [
+                ReplayAction::Cursor(Some(c(2, 2))),
+                ReplayAction::Reveal(c(2, 2))
+            ]
+        );
+    }
+}
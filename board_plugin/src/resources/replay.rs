@@ -0,0 +1,124 @@
+use crate::components::Coordinates;
+use crate::resources::tile_map::TileMap;
+use crate::resources::BoardOptions;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Recorded player action
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ReplayAction {
+    Reveal(Coordinates),
+    Flag(Coordinates),
+    Chord(Coordinates),
+    Undo,
+    Redo,
+    /// The tile pointed by the mouse or the keyboard cursor changed, `None` when off the board
+    Cursor(Option<Coordinates>),
+}
+
+/// Player action with its time since the board was spawned
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    pub time: Duration,
+    pub action: ReplayAction,
+}
+
+/// Recording of a game: its bomb layout and the timestamped player actions.
+/// Must be used as a resource, it is recorded while the running state is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// Options of the recorded board
+    pub options: BoardOptions,
+    /// Bomb layout
+    pub tile_map: TileMap,
+    /// Player actions, oldest first
+    pub events: Vec<ReplayEvent>,
+    /// Recorded time, pauses excluded
+    pub duration: Duration,
+}
+
+impl Replay {
+    /// Empty recording of a new board
+    pub fn new(options: BoardOptions, tile_map: TileMap) -> Self {
+        Self {
+            options,
+            tile_map,
+            events: Vec::new(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    /// Checks a deserialized recording: its map must be valid and its actions must be on the map
+    pub fn validate(&self) -> Result<(), String> {
+        self.tile_map.validate()?;
+        let outside = self.events.iter().find_map(|e| match e.action {
+            ReplayAction::Reveal(c)
+            | ReplayAction::Flag(c)
+            | ReplayAction::Chord(c)
+            | ReplayAction::Cursor(Some(c)) => (!self.tile_map.contains(c)).then_some(c),
+            ReplayAction::Undo | ReplayAction::Redo | ReplayAction::Cursor(None) => None,
+        });
+        match outside {
+            Some(c) => Err(format!("replayed tile {} is out of the map", c)),
+            None => Ok(()),
+        }
+    }
+
+    /// Records `action` at the current time
+    pub fn record(&mut self, action: ReplayAction) {
+        self.events.push(ReplayEvent {
+            time: self.duration,
+            action,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    fn replay(action: ReplayAction) -> Replay {
+        let tile_map = TileMap::with_bombs(3, 2, [c(0, 0)]).unwrap();
+        let mut replay = Replay::new(BoardOptions::default(), tile_map);
+        replay.record(action);
+        replay
+    }
+
+    #[test]
+    fn actions_on_the_map_are_valid() {
+        for action in [
+            ReplayAction::Reveal(c(2, 1)),
+            ReplayAction::Flag(c(0, 0)),
+            ReplayAction::Chord(c(1, 1)),
+            ReplayAction::Cursor(Some(c(2, 0))),
+            ReplayAction::Cursor(None),
+            ReplayAction::Undo,
+            ReplayAction::Redo,
+        ] {
+            assert!(replay(action).validate().is_ok(), "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn actions_out_of_the_map_are_rejected() {
+        for action in [
+            ReplayAction::Reveal(c(3, 0)),
+            ReplayAction::Flag(c(0, 2)),
+            ReplayAction::Chord(c(3, 2)),
+            ReplayAction::Cursor(Some(c(0, 9))),
+        ] {
+            assert!(replay(action).validate().is_err(), "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn invalid_map_is_rejected() {
+        let mut replay = replay(ReplayAction::Undo);
+        replay.tile_map.pop();
+        assert!(replay.validate().is_err());
+    }
+}
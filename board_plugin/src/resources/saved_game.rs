@@ -0,0 +1,452 @@
+use crate::components::Coordinates;
+use crate::events::BoardChangeEvent;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{Board, BoardOptions, GameStatus, History, MoveAction, Replay};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Snapshot of a game in progress, which can be written to a file and resumed.
+///
+/// Inserted as a resource before entering the running state, the board is spawned from it instead
+/// of a new layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// Options of the saved board
+    pub options: BoardOptions,
+    /// Bomb layout
+    pub tile_map: TileMap,
+    pub uncovered_tiles: Vec<Coordinates>,
+    pub marked_tiles: Vec<Coordinates>,
+    /// Bombs uncovered without ending the game
+    pub exploded_tiles: Vec<Coordinates>,
+    /// Remaining lives, `None` when any bomb is fatal
+    pub lives: Option<u8>,
+    /// Played moves, which can still be undone
+    pub history: History,
+    /// Time spent playing
+    pub elapsed: Duration,
+    /// Recording of the game, continued when it is resumed
+    #[serde(default)]
+    pub replay: Option<Replay>,
+}
+
+impl SavedGame {
+    /// Snapshot of the current `board` and its `replay`
+    pub fn new(board: &Board, status: &GameStatus, replay: Option<&Replay>) -> Self {
+        let (width, height) = (board.tile_map.width(), board.tile_map.height());
+        let uncovered_tiles = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Coordinates { x, y }))
+            .filter(|c| !board.covered_tiles.contains_key(c))
+            .collect();
+        Self {
+            options: board.options.clone(),
+            tile_map: board.tile_map.clone(),
+            uncovered_tiles,
+            marked_tiles: board.marked_tiles.clone(),
+            exploded_tiles: board.exploded_tiles.clone(),
+            lives: board.lives,
+            history: board.history.clone(),
+            elapsed: status.elapsed,
+            replay: replay.cloned(),
+        }
+    }
+
+    /// Applies a journaled board change the way the board systems do
+    pub fn apply(&mut self, change: BoardChangeEvent) {
+        match change {
+            BoardChangeEvent::MoveStarted(action) => {
+                self.history.push(action);
+                if let MoveAction::ToggleMark(coords) = action {
+                    self.toggle_mark(coords);
+                }
+            }
+            BoardChangeEvent::TileUncovered {
+                coordinates,
+                move_id,
+            } => {
+                let was_marked = self.unmark(coordinates);
+                if let Some(id) = move_id {
+                    self.history.record_uncover(id, coordinates, was_marked);
+                    if self.tile_map.is_bomb_at(coordinates) {
+                        self.history.record_explosion(id);
+                    }
+                }
+                self.uncover(coordinates);
+            }
+            BoardChangeEvent::Undone => {
+                let m = match self.history.undo() {
+                    None => return,
+                    Some(m) => m,
+                };
+                match m.action {
+                    MoveAction::ToggleMark(coords) => self.toggle_mark(coords),
+                    MoveAction::Reveal(_) | MoveAction::Chord(_) => {
+                        for coords in m.uncovered.iter() {
+                            self.cover(*coords, m.unmarked.contains(coords));
+                        }
+                    }
+                }
+                self.history.push_undone(m);
+            }
+            BoardChangeEvent::Redone => {
+                let m = match self.history.redo() {
+                    None => return,
+                    Some(m) => m,
+                };
+                match m.action {
+                    MoveAction::ToggleMark(coords) => self.toggle_mark(coords),
+                    MoveAction::Reveal(_) | MoveAction::Chord(_) => {
+                        for coords in m.uncovered.iter() {
+                            self.unmark(*coords);
+                            self.uncover(*coords);
+                        }
+                    }
+                }
+                self.history.push_redone(m);
+            }
+        }
+    }
+
+    /// Checks a deserialized game: its map and options must agree and its tiles must be on the map
+    pub fn validate(&self) -> Result<(), String> {
+        self.tile_map.validate()?;
+        let size = (self.tile_map.width(), self.tile_map.height());
+        if self.options.map_size != size || self.options.bomb_count != self.tile_map.bomb_count() {
+            return Err(format!(
+                "options for a {:?} map with {} bombs do not match the {:?} map with {} bombs",
+                self.options.map_size,
+                self.options.bomb_count,
+                size,
+                self.tile_map.bomb_count()
+            ));
+        }
+        let mut tiles = self
+            .uncovered_tiles
+            .iter()
+            .chain(self.marked_tiles.iter())
+            .chain(self.exploded_tiles.iter())
+            .copied()
+            .chain(self.history.coordinates());
+        if let Some(coordinates) = tiles.find(|c| !self.tile_map.contains(*c)) {
+            return Err(format!("tile {} is out of the map", coordinates));
+        }
+        if let Some(replay) = &self.replay {
+            replay.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Is the saved game over, either completed or lost
+    pub fn is_finished(&self) -> bool {
+        let lost = !self.exploded_tiles.is_empty() && self.lives.is_none_or(|lives| lives == 0);
+        let tiles = self.tile_map.width() as usize * self.tile_map.height() as usize;
+        // A tile uncovered twice by a corrupted journal must not underflow
+        let covered = tiles.saturating_sub(self.uncovered_tiles.len());
+        let completed = self.tile_map.bomb_count() as usize == covered + self.exploded_tiles.len();
+        lost || completed
+    }
+
+    /// Uncovers a tile, a bomb costs a life
+    fn uncover(&mut self, coords: Coordinates) {
+        self.uncovered_tiles.push(coords);
+        if self.tile_map.is_bomb_at(coords) {
+            self.exploded_tiles.push(coords);
+            if let Some(lives) = self.lives.as_mut() {
+                *lives = lives.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Covers an uncovered tile back, giving back the life of a bomb
+    fn cover(&mut self, coords: Coordinates, marked: bool) {
+        self.uncovered_tiles.retain(|c| *c != coords);
+        if let Some(pos) = self.exploded_tiles.iter().position(|c| *c == coords) {
+            self.exploded_tiles.remove(pos);
+            if let Some(lives) = self.lives.as_mut() {
+                *lives += 1;
+            }
+        }
+        if marked && !self.marked_tiles.contains(&coords) {
+            self.marked_tiles.push(coords);
+        }
+    }
+
+    fn toggle_mark(&mut self, coords: Coordinates) {
+        if !self.unmark(coords) {
+            self.marked_tiles.push(coords);
+        }
+    }
+
+    /// Removes the mark of a tile, returning `true` if it was marked
+    fn unmark(&mut self, coords: Coordinates) -> bool {
+        let count = self.marked_tiles.len();
+        self.marked_tiles.retain(|c| *c != coords);
+        self.marked_tiles.len() != count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{
+        BoardCompletedEvent, BombExplosionEvent, LifeLostEvent, RedoEvent, TileChordEvent,
+        TileMarkEvent, TileTriggerEvent, UndoEvent,
+    };
+    use crate::resources::tile::Tile;
+    use crate::resources::BoardAssets;
+    use crate::systems::{history, mark, uncover};
+    use crate::{Bomb, BombNeighbor};
+    use bevy::app::Events;
+    use bevy::ecs::event::ManualEventReader;
+    use bevy::prelude::*;
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    /// Game on a 3x3 map with bombs at the bottom left and top right corners, center uncovered
+    fn game() -> SavedGame {
+        let tile_map = TileMap::with_bombs(3, 3, [c(0, 0), c(2, 2)]).unwrap();
+        let mut saved_game = SavedGame::new(
+            &Board::covered(tile_map.clone()),
+            &GameStatus::default(),
+            Some(&Replay::new(BoardOptions::default(), tile_map)),
+        );
+        saved_game.replay.as_mut().unwrap().options = saved_game.options.clone();
+        saved_game.apply(BoardChangeEvent::MoveStarted(MoveAction::Reveal(c(1, 1))));
+        saved_game.apply(BoardChangeEvent::TileUncovered {
+            coordinates: c(1, 1),
+            move_id: Some(0),
+        });
+        saved_game.apply(BoardChangeEvent::MoveStarted(MoveAction::ToggleMark(c(
+            0, 0,
+        ))));
+        saved_game
+    }
+
+    #[test]
+    fn round_trip_keeps_the_game() {
+        let saved_game = game();
+        let content = ron::to_string(&saved_game).unwrap();
+        let loaded: SavedGame = ron::from_str(&content).unwrap();
+        assert!(loaded.validate().is_ok());
+        assert_eq!(ron::to_string(&loaded).unwrap(), content);
+        assert_eq!(loaded.options.map_size, (3, 3));
+        assert_eq!(loaded.uncovered_tiles, vec![c(1, 1)]);
+        assert_eq!(loaded.marked_tiles, vec![c(0, 0)]);
+        assert_eq!(loaded.history.moves().len(), 2);
+        assert_eq!(
+            loaded.tile_map.bombs().collect::<Vec<_>>(),
+            [c(0, 0), c(2, 2)]
+        );
+    }
+
+    #[test]
+    fn missing_rows_are_rejected() {
+        let mut saved_game = game();
+        saved_game.tile_map.pop();
+        assert!(saved_game.validate().is_err());
+        let mut saved_game = game();
+        saved_game.tile_map[1].pop();
+        assert!(saved_game.validate().is_err());
+    }
+
+    #[test]
+    fn wrong_bomb_count_is_rejected() {
+        let mut saved_game = game();
+        saved_game.tile_map[1][1] = Tile::Bomb;
+        assert!(saved_game.validate().is_err());
+        let mut saved_game = game();
+        saved_game.options.bomb_count = 3;
+        assert!(saved_game.validate().is_err());
+    }
+
+    #[test]
+    fn tiles_out_of_the_map_are_rejected() {
+        let mut saved_game = game();
+        saved_game.marked_tiles.push(c(3, 0));
+        assert!(saved_game.validate().is_err());
+        let mut saved_game = game();
+        saved_game.history.push(MoveAction::Reveal(c(0, 3)));
+        assert!(saved_game.validate().is_err());
+        let mut saved_game = game();
+        let replay = saved_game.replay.as_mut().unwrap();
+        replay.record(crate::resources::ReplayAction::Flag(c(9, 9)));
+        assert!(saved_game.validate().is_err());
+    }
+
+    #[test]
+    fn inconsistent_neighbors_are_rejected() {
+        let mut saved_game = game();
+        saved_game.tile_map[1][1] = Tile::Empty;
+        assert!(saved_game.validate().is_err());
+    }
+
+    /// App running the board systems on a 4x4 board with two lives and bombs in the corners of a
+    /// diagonal and in the middle of the top half. Its board changes are applied to a `SavedGame` along the way
+    struct BoardApp {
+        app: App,
+        changes: ManualEventReader<BoardChangeEvent>,
+        saved_game: SavedGame,
+    }
+
+    impl BoardApp {
+        fn new() -> Self {
+            let tile_map = TileMap::with_bombs(4, 4, [c(0, 0), c(3, 3), c(1, 2), c(2, 2)]).unwrap();
+            let mut board = Board::covered(tile_map.clone());
+            board.options.practice_mode = true;
+            board.lives = Some(2);
+            let mut app = App::new();
+            for (coordinates, cover) in board.covered_tiles.iter_mut() {
+                let mut tile = app.world.spawn();
+                tile.insert(*coordinates);
+                match tile_map[coordinates.y as usize][coordinates.x as usize] {
+                    Tile::Bomb => tile.insert(Bomb),
+                    Tile::BombNeighbor(count) => tile.insert(BombNeighbor { count }),
+                    Tile::Empty => &mut tile,
+                };
+                let tile = tile.id();
+                *cover = app.world.spawn().id();
+                app.world.entity_mut(tile).push_children(&[*cover]);
+            }
+            let saved_game = SavedGame::new(&board, &GameStatus::default(), None);
+            app.insert_resource(board)
+                .init_resource::<BoardAssets>()
+                .add_event::<TileTriggerEvent>()
+                .add_event::<TileChordEvent>()
+                .add_event::<TileMarkEvent>()
+                .add_event::<UndoEvent>()
+                .add_event::<RedoEvent>()
+                .add_event::<BoardChangeEvent>()
+                .add_event::<BoardCompletedEvent>()
+                .add_event::<BombExplosionEvent>()
+                .add_event::<LifeLostEvent>()
+                .add_system(uncover::trigger_event_handler)
+                .add_system(uncover::uncover_tiles)
+                .add_system(mark::mark_tiles)
+                .add_system(history::undo_moves)
+                .add_system(history::redo_moves);
+            Self {
+                app,
+                changes: Default::default(),
+                saved_game,
+            }
+        }
+
+        /// Sends `event` and runs frames until its cascade is over, applying the board changes
+        fn play<E: Send + Sync + 'static>(&mut self, event: E) {
+            self.send(event);
+            self.run(10);
+        }
+
+        fn send<E: Send + Sync + 'static>(&mut self, event: E) {
+            self.app
+                .world
+                .get_resource_mut::<Events<E>>()
+                .unwrap()
+                .send(event);
+        }
+
+        /// Runs `frames` frames, applying the board changes
+        fn run(&mut self, frames: usize) {
+            for _ in 0..frames {
+                self.app.update();
+                let changes = self.app.world.get_resource::<Events<BoardChangeEvent>>();
+                for change in self.changes.iter(changes.unwrap()) {
+                    self.saved_game.apply(*change);
+                }
+            }
+        }
+
+        /// Checks the board snapshot matches the game the changes were applied to
+        fn check(&self) {
+            let board = self.app.world.get_resource::<Board>().unwrap();
+            let expected = SavedGame::new(board, &GameStatus::default(), None);
+            let sorted = |tiles: &[Coordinates]| {
+                let mut tiles = tiles.to_vec();
+                tiles.sort();
+                tiles
+            };
+            let applied = &self.saved_game;
+            assert_eq!(
+                sorted(&applied.uncovered_tiles),
+                sorted(&expected.uncovered_tiles)
+            );
+            assert_eq!(
+                sorted(&applied.marked_tiles),
+                sorted(&expected.marked_tiles)
+            );
+            assert_eq!(applied.exploded_tiles, expected.exploded_tiles);
+            assert_eq!(applied.lives, expected.lives);
+            assert_eq!(
+                ron::to_string(&applied.history).unwrap(),
+                ron::to_string(&expected.history).unwrap()
+            );
+            assert_eq!(
+                applied.is_finished(),
+                board.is_completed() || board.is_lost()
+            );
+        }
+    }
+
+    #[test]
+    fn undone_cascade_stops_before_the_next_move() {
+        let mut app = BoardApp::new();
+        app.send(TileTriggerEvent(c(3, 0)));
+        app.run(2);
+        // The cascade is still running
+        app.send(UndoEvent);
+        app.run(1);
+        app.play(TileTriggerEvent(c(0, 3)));
+        app.check();
+        let m = app.saved_game.history.last().unwrap();
+        assert_eq!(m.action, MoveAction::Reveal(c(0, 3)));
+        assert_eq!(m.uncovered, vec![c(0, 3)]);
+        assert_eq!(app.saved_game.uncovered_tiles, vec![c(0, 3)]);
+    }
+
+    #[test]
+    fn applied_changes_match_the_board() {
+        let mut app = BoardApp::new();
+        app.play(TileMarkEvent(c(3, 3)));
+        app.check();
+        // Cascade over several frames
+        app.play(TileTriggerEvent(c(3, 0)));
+        app.check();
+        assert_eq!(app.saved_game.uncovered_tiles.len(), 6);
+        // A bomb costs a life
+        app.play(TileTriggerEvent(c(0, 0)));
+        app.check();
+        assert_eq!(app.saved_game.lives, Some(1));
+        app.play(UndoEvent);
+        app.check();
+        app.play(RedoEvent);
+        app.check();
+        app.play(UndoEvent);
+        app.play(UndoEvent);
+        app.check();
+        app.play(RedoEvent);
+        app.check();
+        // A new move drops the redo stack
+        app.play(TileMarkEvent(c(0, 0)));
+        app.play(RedoEvent);
+        app.check();
+        // A chord is a single move
+        app.play(TileChordEvent(c(1, 0)));
+        app.check();
+        let chord = app.saved_game.history.last().unwrap();
+        assert_eq!(chord.action, MoveAction::Chord(c(1, 0)));
+        assert_eq!(chord.uncovered, vec![c(0, 1)]);
+        app.play(UndoEvent);
+        app.check();
+        app.play(RedoEvent);
+        app.check();
+        assert!(!app.saved_game.is_finished());
+        // The remaining safe tiles complete the board
+        for (x, y) in [(0, 2), (3, 2), (0, 3), (1, 3), (2, 3)] {
+            app.play(TileTriggerEvent(c(x, y)));
+        }
+        app.check();
+        assert!(app.saved_game.is_finished());
+    }
+}
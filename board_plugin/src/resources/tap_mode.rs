@@ -0,0 +1,31 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Action of a short tap on a touch screen, a long press does the other one.
+/// Must be used as a resource
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum TapMode {
+    /// Taps reveal tiles and long presses flag them
+    #[default]
+    Reveal,
+    /// Taps flag tiles and long presses reveal them
+    Flag,
+}
+
+impl TapMode {
+    /// The other tap mode
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Reveal => Self::Flag,
+            Self::Flag => Self::Reveal,
+        }
+    }
+}
+
+impl Display for TapMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reveal => write!(f, "Reveal"),
+            Self::Flag => write!(f, "Flag"),
+        }
+    }
+}
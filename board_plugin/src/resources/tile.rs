@@ -1,7 +1,8 @@
 #[cfg(feature = "debug")]
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Tile {
     /// Is a Bomb
     Bomb,
@@ -1,6 +1,7 @@
 use crate::components::Coordinates;
 use crate::resources::tile::Tile;
 use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 
 /// Delta coordinates for all 8 square neighbors
@@ -24,7 +25,7 @@ const SQUARE_COORDINATES: [(i8, i8); 8] = [
 ];
 
 /// Base tile map
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileMap {
     bomb_count: u16,
     height: u16,
@@ -50,8 +51,7 @@ impl TileMap {
     /// Generates an empty map
     pub fn empty(width: u16, height: u16) -> Self {
         let map = (0..height)
-            .into_iter()
-            .map(|_| (0..width).into_iter().map(|_| Tile::Empty).collect())
+            .map(|_| (0..width).map(|_| Tile::Empty).collect())
             .collect();
         Self {
             bomb_count: 0,
@@ -89,6 +89,43 @@ impl TileMap {
         self.bomb_count
     }
 
+    /// Is `coordinates` on the map
+    pub fn contains(&self, coordinates: Coordinates) -> bool {
+        coordinates.x < self.width && coordinates.y < self.height
+    }
+
+    /// Checks a deserialized map: its size, its bomb count and its neighbor tiles must match
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!("empty {}x{} map", self.width, self.height));
+        }
+        if self.map.len() != self.height as usize
+            || self.map.iter().any(|row| row.len() != self.width as usize)
+        {
+            return Err(format!(
+                "rows do not match the {}x{} map",
+                self.width, self.height
+            ));
+        }
+        let bombs = self.bombs().count();
+        if bombs != self.bomb_count as usize {
+            return Err(format!("{} bombs instead of {}", bombs, self.bomb_count));
+        }
+        for coordinates in (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Coordinates { x, y }))
+            .filter(|c| !self.is_bomb_at(*c))
+        {
+            let expected = match self.bomb_count_at(coordinates) {
+                0 => Tile::Empty,
+                num => Tile::BombNeighbor(num),
+            };
+            if self[coordinates.y as usize][coordinates.x as usize] != expected {
+                return Err(format!("tile {} does not match its neighbors", coordinates));
+            }
+        }
+        Ok(())
+    }
+
     pub fn safe_square_at(&self, coordinates: Coordinates) -> impl Iterator<Item = Coordinates> {
         SQUARE_COORDINATES
             .iter()
@@ -129,7 +166,43 @@ impl TileMap {
                 remaining_bombs -= 1;
             }
         }
-        // calc bomb neighbors
+        self.set_neighbors();
+    }
+
+    /// Builds a `width` x `height` map with bombs at `bombs`
+    pub fn with_bombs(
+        width: u16,
+        height: u16,
+        bombs: impl IntoIterator<Item = Coordinates>,
+    ) -> Result<Self, String> {
+        let mut tile_map = Self::empty(width, height);
+        for coordinates in bombs {
+            if coordinates.x >= width || coordinates.y >= height {
+                return Err(format!("bomb {} is out of the map", coordinates));
+            }
+            let tile = &mut tile_map[coordinates.y as usize][coordinates.x as usize];
+            if tile.is_bomb() {
+                return Err(format!("bomb {} is set twice", coordinates));
+            }
+            *tile = Tile::Bomb;
+            tile_map.bomb_count = tile_map
+                .bomb_count
+                .checked_add(1)
+                .ok_or_else(|| format!("more than {} bombs", u16::MAX))?;
+        }
+        tile_map.set_neighbors();
+        Ok(tile_map)
+    }
+
+    /// Coordinates of the bombs, row by row from the bottom
+    pub fn bombs(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| Coordinates { x, y }))
+            .filter(|coordinates| self.is_bomb_at(*coordinates))
+    }
+
+    /// Computes the bomb neighbor tiles from the laid bombs
+    fn set_neighbors(&mut self) {
         for y in 0..self.height {
             for x in 0..self.width {
                 let coords = Coordinates { x, y };
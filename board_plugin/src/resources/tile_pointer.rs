@@ -0,0 +1,10 @@
+use crate::components::Coordinates;
+
+/// Board tiles under the mouse cursor, drawn by the tile highlights. Must be used as a resource
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TilePointer {
+    /// Tile under the mouse cursor
+    pub hovered: Option<Coordinates>,
+    /// Covered tiles drawn as pressed, which the held mouse button acts on when released
+    pub pressed: Vec<Coordinates>,
+}
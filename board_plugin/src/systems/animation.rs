@@ -0,0 +1,115 @@
+use crate::events::{BombExplosionEvent, LifeLostEvent, SkipAnimations};
+use crate::{Board, BoardOptions, Tween, TweenKind, Uncover};
+use bevy::prelude::*;
+
+/// Delay between two depths of an uncovering cascade, in seconds
+const RIPPLE_DELAY: f32 = 0.03;
+/// Cover shrinking duration, in seconds
+const REVEAL_DURATION: f32 = 0.15;
+/// Flag pop in and out duration, in seconds
+const FLAG_DURATION: f32 = 0.15;
+/// Board shake duration, in seconds
+const SHAKE_DURATION: f32 = 0.35;
+/// Board shake amplitude, relative to the tile size
+const SHAKE_AMPLITUDE: f32 = 0.2;
+
+/// Shrinks and despawns an uncovered tile `cover`, later for deeper tiles of a cascade
+pub(crate) fn reveal_cover(commands: &mut Commands, cover: Entity, depth: u16) {
+    let tween = Tween::new(
+        TweenKind::ShrinkOut,
+        depth as f32 * RIPPLE_DELAY,
+        REVEAL_DURATION,
+    );
+    commands.entity(cover).remove::<Uncover>().insert(tween);
+}
+
+/// Tween popping a newly spawned flag in
+pub(crate) fn flag_in_tween() -> Tween {
+    Tween::new(TweenKind::PopIn, 0., FLAG_DURATION)
+}
+
+/// Tween popping a flag out before despawning it
+pub(crate) fn flag_out_tween() -> Tween {
+    Tween::new(TweenKind::ShrinkOut, 0., FLAG_DURATION)
+}
+
+/// Shakes the board when a bomb explodes
+pub fn shake_board(
+    mut commands: Commands,
+    board: Res<Board>,
+    mut tweens: Query<&mut Tween>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut life_lost_evr: EventReader<LifeLostEvent>,
+) {
+    let exploded = bomb_explosion_evr.iter().count() > 0;
+    let life_lost = life_lost_evr.iter().count() > 0;
+    if !exploded && !life_lost {
+        return;
+    }
+    // A running shake is restarted, replacing it would lose its current offset
+    if let Ok(mut tween) = tweens.get_mut(board.entity) {
+        tween.elapsed = 0.;
+        return;
+    }
+    let kind = TweenKind::Shake {
+        amplitude: board.tile_size * SHAKE_AMPLITUDE,
+        offset: Vec3::ZERO,
+    };
+    commands
+        .entity(board.entity)
+        .insert(Tween::new(kind, 0., SHAKE_DURATION));
+}
+
+/// Plays the tweens, finishing them at once when skipped or disabled.
+///
+/// Runs after the commands of the update stage so new tweens are applied before their first frame
+pub fn animate(
+    mut commands: Commands,
+    time: Res<Time>,
+    board_options: Option<Res<BoardOptions>>,
+    mut skip_evr: EventReader<SkipAnimations>,
+    mut tweens: Query<(Entity, &mut Tween, &mut Transform)>,
+) {
+    let disabled = board_options.is_some_and(|o| !o.animations);
+    let skip = skip_evr.iter().count() > 0;
+    for (entity, mut tween, mut transform) in tweens.iter_mut() {
+        // Tweens added since the skip request, by the skipping click for example, are played
+        let finish = disabled || (skip && tween.elapsed > 0.);
+        tween.elapsed += time.delta_seconds();
+        let elapsed = tween.elapsed;
+        let progress = if finish { 1. } else { tween.progress() };
+        match &mut tween.kind {
+            TweenKind::ShrinkOut => {
+                let scale = 1. - progress * progress;
+                transform.scale = Vec3::new(scale, scale, 1.);
+            }
+            TweenKind::PopIn => {
+                let scale = back_out(progress);
+                transform.scale = Vec3::new(scale, scale, 1.);
+            }
+            TweenKind::Shake { amplitude, offset } => {
+                transform.translation -= *offset;
+                let t = elapsed * 60.;
+                *offset = Vec3::new(t.sin(), (t * 0.8).cos(), 0.) * *amplitude * (1. - progress);
+                transform.translation += *offset;
+            }
+        }
+        if progress < 1. {
+            continue;
+        }
+        match tween.kind {
+            TweenKind::ShrinkOut => commands.entity(entity).despawn_recursive(),
+            _ => {
+                commands.entity(entity).remove::<Tween>();
+            }
+        }
+    }
+}
+
+/// Easing overshooting its target before settling
+fn back_out(progress: f32) -> f32 {
+    const C1: f32 = 1.70158;
+    const C3: f32 = C1 + 1.;
+    let p = progress - 1.;
+    1. + C3 * p * p * p + C1 * p * p
+}
@@ -0,0 +1,205 @@
+use crate::resources::{ActionInput, InputAction};
+use crate::{Board, BoardCamera};
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Keyboard panning speed, in window pixels per second
+const PAN_SPEED: f32 = 500.;
+/// Zoom factor of a mouse wheel line
+const ZOOM_STEP: f32 = 1.1;
+/// Mouse wheel pixels counted as one line
+const PIXELS_PER_LINE: f32 = 50.;
+/// Camera projection scale limits, a lower scale zooms in
+const MIN_SCALE: f32 = 0.2;
+const MAX_SCALE: f32 = 4.;
+
+/// Pans the board camera with the pan actions or a camera drag and zooms it with the mouse wheel.
+///
+/// The camera is reset when a new board is spawned
+pub fn camera_controls(
+    windows: Res<Windows>,
+    time: Res<Time>,
+    board: Res<Board>,
+    input: ActionInput,
+    mut motion_evr: EventReader<MouseMotion>,
+    mut wheel_evr: EventReader<MouseWheel>,
+    mut cameras: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &mut Transform,
+            &mut OrthographicProjection,
+        ),
+        With<BoardCamera>,
+    >,
+    mut last_board: Local<Option<Entity>>,
+) {
+    let (camera, global_transform, mut transform, mut projection) = match cameras.get_single_mut() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    if *last_board != Some(board.entity) {
+        *last_board = Some(board.entity);
+        transform.translation.x = 0.;
+        transform.translation.y = 0.;
+        projection.scale = 1.;
+        return;
+    }
+
+    // Panning, window distances are scaled to world space
+    let mut pan = Vec2::ZERO;
+    for (action, direction) in [
+        (InputAction::PanUp, Vec2::Y),
+        (InputAction::PanLeft, -Vec2::X),
+        (InputAction::PanDown, -Vec2::Y),
+        (InputAction::PanRight, Vec2::X),
+    ] {
+        if input.pressed(action) {
+            pan += direction * PAN_SPEED * time.delta_seconds();
+        }
+    }
+    let dragging = input.pressed(InputAction::DragCamera);
+    for motion in motion_evr.iter() {
+        if dragging {
+            // The window y axis points down
+            pan += Vec2::new(-motion.delta.x, motion.delta.y);
+        }
+    }
+    transform.translation += (pan * projection.scale).extend(0.);
+
+    // Zooming, the world position under the cursor stays in place
+    let lines: f32 = wheel_evr
+        .iter()
+        .map(|e| match e.unit {
+            MouseScrollUnit::Line => e.y,
+            MouseScrollUnit::Pixel => e.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    if lines != 0. {
+        let window = windows.get_primary().unwrap();
+        let anchor = window.cursor_position();
+        let scale = projection.scale * ZOOM_STEP.powf(-lines);
+        zoom(
+            window,
+            camera,
+            global_transform,
+            &mut transform,
+            &mut projection,
+            scale,
+            anchor,
+        );
+    }
+    clamp_to_board(&board, &mut transform);
+}
+
+/// Pans the board camera with a two finger drag and zooms it with a pinch
+pub fn touch_camera(
+    windows: Res<Windows>,
+    board: Res<Board>,
+    mut touch_evr: EventReader<TouchInput>,
+    mut cameras: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &mut Transform,
+            &mut OrthographicProjection,
+        ),
+        With<BoardCamera>,
+    >,
+    mut touches: Local<HashMap<u64, Vec2>>,
+) {
+    let (camera, global_transform, mut transform, mut projection) = match cameras.get_single_mut() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let window = match windows.get_primary() {
+        Some(w) => w,
+        None => return,
+    };
+    // The gesture of the frame goes from the first to the last two finger span
+    let mut start: Option<Vec2> = None;
+    let mut end = Vec2::ZERO;
+    let mut ratio = 1.;
+    for event in touch_evr.iter() {
+        let previous = two_finger_span(&touches);
+        match event.phase {
+            TouchPhase::Started | TouchPhase::Moved => {
+                touches.insert(event.id, event.position);
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                touches.remove(&event.id);
+                continue;
+            }
+        }
+        if event.phase != TouchPhase::Moved {
+            continue;
+        }
+        if let (Some((previous_center, previous_distance)), Some((center, distance))) =
+            (previous, two_finger_span(&touches))
+        {
+            start.get_or_insert(previous_center);
+            end = center;
+            if previous_distance > 0. && distance > 0. {
+                ratio *= previous_distance / distance;
+            }
+        }
+    }
+    let start = match start {
+        Some(s) => s,
+        None => return,
+    };
+    // Zooming around the fingers, then moving the world along them.
+    // Touch positions have the same axes as the world
+    let scale = projection.scale * ratio;
+    zoom(
+        window,
+        camera,
+        global_transform,
+        &mut transform,
+        &mut projection,
+        scale,
+        Some(start),
+    );
+    transform.translation += ((start - end) * projection.scale).extend(0.);
+    clamp_to_board(&board, &mut transform);
+}
+
+/// Center and distance of the two fingers on the screen, `None` for any other finger count
+fn two_finger_span(touches: &HashMap<u64, Vec2>) -> Option<(Vec2, f32)> {
+    if touches.len() != 2 {
+        return None;
+    }
+    let mut positions = touches.values();
+    let (a, b) = (*positions.next()?, *positions.next()?);
+    Some(((a + b) / 2., a.distance(b)))
+}
+
+/// Sets the camera `scale`, keeping the world position under the window `anchor` in place
+fn zoom(
+    window: &Window,
+    camera: &Camera,
+    global_transform: &GlobalTransform,
+    transform: &mut Transform,
+    projection: &mut OrthographicProjection,
+    scale: f32,
+    anchor: Option<Vec2>,
+) {
+    let scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+    if let Some(anchor) = anchor {
+        let anchor = BoardCamera::cursor_to_world(window, camera, global_transform, anchor);
+        let offset = transform.translation.truncate() - anchor;
+        let center = anchor + offset * scale / projection.scale;
+        transform.translation = center.extend(transform.translation.z);
+    }
+    projection.scale = scale;
+}
+
+/// Keeps the camera center over the board
+fn clamp_to_board(board: &Board, transform: &mut Transform) {
+    let min = board.bounds.position;
+    let max = board.bounds.position + board.bounds.size;
+    transform.translation.x = transform.translation.x.clamp(min.x, max.x);
+    transform.translation.y = transform.translation.y.clamp(min.y, max.y);
+}
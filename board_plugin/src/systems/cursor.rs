@@ -0,0 +1,221 @@
+use crate::events::{
+    CursorAction, SkipAnimations, TileChordEvent, TileMarkEvent, TileTriggerEvent,
+};
+use crate::resources::{ActionInput, InputAction, InputDevice};
+use crate::{Board, Coordinates, TileCursor};
+use bevy::log;
+use bevy::prelude::*;
+
+/// The cursor is drawn above the tiles, flags and covers
+const CURSOR_Z: f32 = 5.;
+
+/// Board space transform of the tile cursor at `coordinates`
+pub(crate) fn cursor_transform(coordinates: Coordinates, tile_size: f32) -> Transform {
+    Transform::from_xyz(
+        (coordinates.x as f32 + 0.5) * tile_size,
+        (coordinates.y as f32 + 0.5) * tile_size,
+        CURSOR_Z,
+    )
+}
+
+/// Sends the keyboard cursor actions, with the default `InputBindings`:
+/// - arrows or `hjkl` move the cursor, jumping to the board edge with shift
+/// - `Tab` jumps to the next covered tile, shift jumps to the previous one
+/// - `Space` reveals, `F` flags and `Enter` chords the tile under the cursor
+pub fn keyboard_cursor(input: ActionInput, mut cursor_action_ewr: EventWriter<CursorAction>) {
+    let just_pressed = |action| input.just_pressed_on(action, InputDevice::Keyboard);
+    let jump = input.pressed_on(InputAction::CursorJump, InputDevice::Keyboard);
+    for (action, (x, y)) in [
+        (InputAction::CursorLeft, (-1, 0)),
+        (InputAction::CursorRight, (1, 0)),
+        (InputAction::CursorUp, (0, 1)),
+        (InputAction::CursorDown, (0, -1)),
+    ] {
+        if just_pressed(action) {
+            cursor_action_ewr.send(match jump {
+                true => CursorAction::MoveToEdge { x, y },
+                false => CursorAction::Move { x, y },
+            });
+        }
+    }
+    if just_pressed(InputAction::NextCoveredTile) {
+        cursor_action_ewr.send(match jump {
+            true => CursorAction::PreviousCoveredTile,
+            false => CursorAction::NextCoveredTile,
+        });
+    }
+    for (input_action, action) in [
+        (InputAction::Reveal, CursorAction::Reveal),
+        (InputAction::Flag, CursorAction::Flag),
+        (InputAction::Chord, CursorAction::Chord),
+    ] {
+        if just_pressed(input_action) {
+            cursor_action_ewr.send(action);
+        }
+    }
+}
+
+/// Moves the tile cursor and turns its actions into tile events.
+///
+/// The cursor is shown by the cursor actions and hidden by mouse clicks
+pub fn apply_cursor_actions(
+    board: Res<Board>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut cursor_action_evr: EventReader<CursorAction>,
+    mut cursors: Query<(&mut TileCursor, &mut Transform, &mut Visibility)>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
+    mut skip_animations_ewr: EventWriter<SkipAnimations>,
+) {
+    let (mut cursor, mut transform, mut visibility) = match cursors.get_single_mut() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    if mouse_buttons.get_just_pressed().next().is_some() {
+        visibility.is_visible = false;
+    }
+    for action in cursor_action_evr.iter() {
+        visibility.is_visible = true;
+        skip_animations_ewr.send(SkipAnimations);
+        let coords = cursor.coordinates;
+        match *action {
+            CursorAction::Move { .. }
+            | CursorAction::MoveToEdge { .. }
+            | CursorAction::NextCoveredTile
+            | CursorAction::PreviousCoveredTile => {
+                cursor.coordinates = moved_cursor(&board, coords, *action);
+            }
+            CursorAction::Reveal => {
+                log::info!("Trying to uncover tile on {}", coords);
+                tile_trigger_ewr.send(TileTriggerEvent(coords));
+            }
+            CursorAction::Flag => {
+                log::info!("Trying to mark tile on {}", coords);
+                tile_mark_ewr.send(TileMarkEvent(coords));
+            }
+            CursorAction::Chord => {
+                log::info!("Trying to chord tile on {}", coords);
+                tile_chord_ewr.send(TileChordEvent(coords));
+            }
+        }
+    }
+    // The cursor follows the board layout
+    *transform = cursor_transform(cursor.coordinates, board.tile_size);
+}
+
+/// Cursor position after a move `action` from `coords`, which stays on the board
+fn moved_cursor(board: &Board, coords: Coordinates, action: CursorAction) -> Coordinates {
+    let (width, height) = (board.tile_map.width(), board.tile_map.height());
+    match action {
+        CursorAction::Move { x, y } => {
+            let step = |v: u16, d: i8, max: u16| (v as i32 + d as i32).clamp(0, max as i32 - 1);
+            Coordinates {
+                x: step(coords.x, x, width) as u16,
+                y: step(coords.y, y, height) as u16,
+            }
+        }
+        CursorAction::MoveToEdge { x, y } => {
+            let edge = |v: u16, d: i8, max: u16| match d.signum() {
+                -1 => 0,
+                1 => max - 1,
+                _ => v,
+            };
+            Coordinates {
+                x: edge(coords.x, x, width),
+                y: edge(coords.y, y, height),
+            }
+        }
+        CursorAction::NextCoveredTile | CursorAction::PreviousCoveredTile => {
+            let forward = action == CursorAction::NextCoveredTile;
+            next_covered_tile(board, coords, forward).unwrap_or(coords)
+        }
+        CursorAction::Reveal | CursorAction::Flag | CursorAction::Chord => coords,
+    }
+}
+
+/// Finds the next covered and unmarked tile after `from` in reading order, `forward` or backward
+fn next_covered_tile(board: &Board, from: Coordinates, forward: bool) -> Option<Coordinates> {
+    let (width, height) = (
+        board.tile_map.width() as usize,
+        board.tile_map.height() as usize,
+    );
+    let count = width * height;
+    // Reading order goes from the top left tile, the board `y` axis points up
+    let index = (height - 1 - from.y as usize) * width + from.x as usize;
+    (1..count)
+        .map(|offset| match forward {
+            true => (index + offset) % count,
+            false => (index + count - offset) % count,
+        })
+        .map(|i| Coordinates {
+            x: (i % width) as u16,
+            y: (height - 1 - i / width) as u16,
+        })
+        .find(|c| board.covered_tiles.contains_key(c) && !board.marked_tiles.contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileMap;
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    /// 4x3 board with a bomb at the top left corner and every tile covered
+    fn board() -> Board {
+        Board::covered(TileMap::with_bombs(4, 3, [c(0, 2)]).unwrap())
+    }
+
+    #[test]
+    fn move_stays_on_the_board() {
+        let board = board();
+        let right = CursorAction::Move { x: 1, y: 0 };
+        let down = CursorAction::Move { x: 0, y: -1 };
+        assert_eq!(moved_cursor(&board, c(1, 1), right), c(2, 1));
+        assert_eq!(moved_cursor(&board, c(3, 1), right), c(3, 1));
+        assert_eq!(moved_cursor(&board, c(1, 0), down), c(1, 0));
+    }
+
+    #[test]
+    fn move_to_edge_jumps_to_the_board_edge() {
+        let board = board();
+        let left = CursorAction::MoveToEdge { x: -1, y: 0 };
+        let up = CursorAction::MoveToEdge { x: 0, y: 1 };
+        assert_eq!(moved_cursor(&board, c(2, 1), left), c(0, 1));
+        assert_eq!(moved_cursor(&board, c(2, 1), up), c(2, 2));
+    }
+
+    #[test]
+    fn next_covered_tile_follows_reading_order() {
+        let mut board = board();
+        let next = CursorAction::NextCoveredTile;
+        let previous = CursorAction::PreviousCoveredTile;
+        // Reading order goes to the next row down at the end of a row
+        assert_eq!(moved_cursor(&board, c(3, 2), next), c(0, 1));
+        board.uncover_tile(&c(1, 1));
+        board.try_toggle_mark(&c(2, 1));
+        assert_eq!(moved_cursor(&board, c(0, 1), next), c(3, 1));
+        assert_eq!(moved_cursor(&board, c(3, 1), previous), c(0, 1));
+    }
+
+    #[test]
+    fn next_covered_tile_wraps_around() {
+        let board = board();
+        let next = CursorAction::NextCoveredTile;
+        let previous = CursorAction::PreviousCoveredTile;
+        assert_eq!(moved_cursor(&board, c(3, 0), next), c(0, 2));
+        assert_eq!(moved_cursor(&board, c(0, 2), previous), c(3, 0));
+    }
+
+    #[test]
+    fn cursor_stays_without_other_covered_tile() {
+        let mut board = Board::covered(TileMap::with_bombs(2, 1, [c(0, 0)]).unwrap());
+        board.uncover_tile(&c(1, 0));
+        let next = CursorAction::NextCoveredTile;
+        assert_eq!(moved_cursor(&board, c(0, 0), next), c(0, 0));
+        assert_eq!(moved_cursor(&board, c(1, 0), next), c(0, 0));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::events::CursorAction;
+use crate::resources::{ActionInput, InputAction, InputDevice};
+use bevy::prelude::*;
+
+/// Stick deflection needed to move the cursor
+const STICK_THRESHOLD: f32 = 0.5;
+/// Seconds before a held direction starts repeating
+const REPEAT_DELAY: f32 = 0.35;
+/// Seconds between two repeated moves
+const REPEAT_INTERVAL: f32 = 0.08;
+
+/// Held stick or D-pad direction of the tile cursor
+#[derive(Debug, Default)]
+pub struct DirectionRepeat {
+    direction: (i8, i8),
+    /// Seconds until the next repeated move
+    timer: f32,
+}
+
+/// Sends the gamepad cursor actions, with the default `InputBindings`:
+/// - the D-pad and the left stick move the cursor, repeating while held
+/// - south reveals, east flags and west chords the tile under the cursor
+pub fn gamepad_cursor(
+    time: Res<Time>,
+    gamepads: Res<Gamepads>,
+    input: ActionInput,
+    axes: Res<Axis<GamepadAxis>>,
+    mut repeat: Local<DirectionRepeat>,
+    mut cursor_action_ewr: EventWriter<CursorAction>,
+) {
+    let pressed = |action| input.pressed_on(action, InputDevice::Gamepad);
+    let mut direction = (0, 0);
+    for gamepad in gamepads.iter().copied() {
+        let axis = |axis| axes.get(GamepadAxis(gamepad, axis)).unwrap_or_default();
+        let stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if stick.x <= -STICK_THRESHOLD {
+            direction.0 = -1;
+        }
+        if stick.x >= STICK_THRESHOLD {
+            direction.0 = 1;
+        }
+        if stick.y <= -STICK_THRESHOLD {
+            direction.1 = -1;
+        }
+        if stick.y >= STICK_THRESHOLD {
+            direction.1 = 1;
+        }
+    }
+    if pressed(InputAction::CursorLeft) {
+        direction.0 = -1;
+    }
+    if pressed(InputAction::CursorRight) {
+        direction.0 = 1;
+    }
+    if pressed(InputAction::CursorDown) {
+        direction.1 = -1;
+    }
+    if pressed(InputAction::CursorUp) {
+        direction.1 = 1;
+    }
+    for (input_action, action) in [
+        (InputAction::Reveal, CursorAction::Reveal),
+        (InputAction::Flag, CursorAction::Flag),
+        (InputAction::Chord, CursorAction::Chord),
+    ] {
+        if input.just_pressed_on(input_action, InputDevice::Gamepad) {
+            cursor_action_ewr.send(action);
+        }
+    }
+
+    // A new direction moves at once, a held one repeats after a delay
+    if direction != repeat.direction {
+        repeat.direction = direction;
+        repeat.timer = REPEAT_DELAY;
+    } else {
+        repeat.timer -= time.delta_seconds();
+        if repeat.timer > 0. {
+            return;
+        }
+        repeat.timer += REPEAT_INTERVAL;
+    }
+    if direction != (0, 0) {
+        let (x, y) = direction;
+        cursor_action_ewr.send(CursorAction::Move { x, y });
+    }
+}
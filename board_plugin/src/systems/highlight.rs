@@ -0,0 +1,74 @@
+use crate::resources::TilePointer;
+use crate::systems::cursor::cursor_transform;
+use crate::{Board, BoardAssets, Coordinates, HoverHighlight, PressedTile, Themed};
+use bevy::prelude::*;
+
+/// Pressed tiles are drawn above the covers, the hover highlight above them
+const PRESSED_Z: f32 = 3.;
+const HOVER_Z: f32 = 4.;
+
+/// Draws the `TilePointer`: the hover highlight over the hovered covered tile and the pressed
+/// look of the pressed tiles
+pub fn update_tile_highlights(
+    mut commands: Commands,
+    board: Res<Board>,
+    board_assets: Res<BoardAssets>,
+    pointer: Res<TilePointer>,
+    mut hovers: Query<(&mut Transform, &mut Visibility), With<HoverHighlight>>,
+    mut pressed_tiles: Query<(Entity, &PressedTile, &mut Transform), Without<HoverHighlight>>,
+) {
+    let hovered = pointer
+        .hovered
+        .filter(|c| board.covered_tiles.contains_key(c));
+    for (mut transform, mut visibility) in hovers.iter_mut() {
+        if visibility.is_visible != hovered.is_some() {
+            visibility.is_visible = hovered.is_some();
+        }
+        if let Some(coordinates) = hovered {
+            *transform = highlight_transform(coordinates, board.tile_size, HOVER_Z);
+        }
+    }
+
+    // Pressed tiles follow the board layout and disappear with their cover
+    let mut shown = Vec::new();
+    for (entity, pressed, mut transform) in pressed_tiles.iter_mut() {
+        let coordinates = pressed.coordinates;
+        if pointer.pressed.contains(&coordinates) && board.covered_tiles.contains_key(&coordinates)
+        {
+            *transform = highlight_transform(coordinates, board.tile_size, PRESSED_Z);
+            shown.push(coordinates);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let missing: Vec<Coordinates> = pointer
+        .pressed
+        .iter()
+        .filter(|c| !shown.contains(c))
+        .copied()
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    let size = Vec2::splat(board.tile_size - board.tile_padding);
+    commands.entity(board.entity).with_children(|parent| {
+        for coordinates in missing {
+            let mut pressed = parent.spawn();
+            board_assets.insert_themed(
+                &mut pressed,
+                Themed::Pressed,
+                size,
+                highlight_transform(coordinates, board.tile_size, PRESSED_Z),
+            );
+            pressed
+                .insert(Name::new("Pressed Tile"))
+                .insert(PressedTile { coordinates });
+        }
+    });
+}
+
+fn highlight_transform(coordinates: Coordinates, tile_size: f32, z: f32) -> Transform {
+    let mut transform = cursor_transform(coordinates, tile_size);
+    transform.translation.z = z;
+    transform
+}
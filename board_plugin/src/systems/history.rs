@@ -0,0 +1,156 @@
+use crate::events::{BoardChangeEvent, RedoEvent, UndoEvent};
+use crate::resources::MoveAction;
+use crate::systems::animation::reveal_cover;
+use crate::systems::mark::update_flag;
+use crate::systems::uncover::spawn_exploded_flag;
+use crate::{Board, BoardAssets, Coordinates, ExplodedFlag};
+use crate::{BoardCompletedEvent, BombExplosionEvent, LifeLostEvent};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+pub fn undo_moves(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    board_assets: Res<BoardAssets>,
+    mut undo_evr: EventReader<UndoEvent>,
+    tiles: Query<(Entity, &Coordinates)>,
+    children: Query<&Children>,
+    exploded_flags: Query<(Entity, &Parent), With<ExplodedFlag>>,
+    mut board_change_ewr: EventWriter<BoardChangeEvent>,
+) {
+    let practice_mode = board.options.practice_mode;
+    let mut tile_entities: Option<HashMap<Coordinates, Entity>> = None;
+    for _ in undo_evr.iter() {
+        match board.history.last() {
+            None => {
+                log::debug!("Nothing to undo");
+                continue;
+            }
+            Some(m) if m.exploded && !practice_mode => {
+                log::info!("Undoing a bomb explosion is only allowed in practice mode");
+                continue;
+            }
+            Some(_) => (),
+        }
+        let m = match board.history.undo() {
+            None => continue,
+            Some(m) => m,
+        };
+        match m.action {
+            MoveAction::ToggleMark(coords) => {
+                if let Some((entity, mark)) = board.try_toggle_mark(&coords) {
+                    update_flag(
+                        &mut commands,
+                        entity,
+                        mark,
+                        board.tile_size,
+                        &board_assets,
+                        &children,
+                    );
+                }
+            }
+            MoveAction::Reveal(_) | MoveAction::Chord(_) => {
+                // Tile entities are only looked up when a cover must be restored
+                let tile_entities = tile_entities
+                    .get_or_insert_with(|| tiles.iter().map(|(e, c)| (*c, e)).collect());
+                let cover_size = Vec2::splat(board.tile_size - board.tile_padding);
+                for coords in m.uncovered.iter() {
+                    let tile = match tile_entities.get(coords) {
+                        Some(e) => *e,
+                        None => {
+                            log::error!("Failed to retrieve tile entity at {}", coords);
+                            continue;
+                        }
+                    };
+                    let mut cover = commands.spawn();
+                    board_assets.insert_covered_tile(&mut cover, cover_size);
+                    let cover = cover.id();
+                    commands.entity(tile).push_children(&[cover]);
+                    if board.restore_bomb(coords) {
+                        for (flag, _) in exploded_flags.iter().filter(|(_, p)| p.0 == tile) {
+                            commands.entity(flag).despawn_recursive();
+                        }
+                    }
+                    let marked = m.unmarked.contains(coords);
+                    if marked {
+                        update_flag(
+                            &mut commands,
+                            cover,
+                            true,
+                            board.tile_size,
+                            &board_assets,
+                            &children,
+                        );
+                    }
+                    board.cover_tile(*coords, cover, marked);
+                }
+            }
+        }
+        log::info!("Undone move {:?}", m.action);
+        board.history.push_undone(m);
+        board_change_ewr.send(BoardChangeEvent::Undone);
+    }
+}
+
+pub fn redo_moves(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    board_assets: Res<BoardAssets>,
+    mut redo_evr: EventReader<RedoEvent>,
+    tiles: Query<(Entity, &Coordinates)>,
+    children: Query<&Children>,
+    mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
+    mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+    mut life_lost_event_wr: EventWriter<LifeLostEvent>,
+    mut board_change_ewr: EventWriter<BoardChangeEvent>,
+) {
+    for _ in redo_evr.iter() {
+        let m = match board.history.redo() {
+            None => {
+                log::debug!("Nothing to redo");
+                continue;
+            }
+            Some(m) => m,
+        };
+        match m.action {
+            MoveAction::ToggleMark(coords) => {
+                if let Some((entity, mark)) = board.try_toggle_mark(&coords) {
+                    update_flag(
+                        &mut commands,
+                        entity,
+                        mark,
+                        board.tile_size,
+                        &board_assets,
+                        &children,
+                    );
+                }
+            }
+            MoveAction::Reveal(_) | MoveAction::Chord(_) => {
+                for coords in m.uncovered.iter() {
+                    if let Some(entity) = board.uncover_tile(coords) {
+                        reveal_cover(&mut commands, entity, 0);
+                    }
+                    if !board.tile_map.is_bomb_at(*coords) {
+                        continue;
+                    }
+                    if board.explode_bomb(*coords) {
+                        bomb_explosion_event_wr.send(BombExplosionEvent(*coords));
+                    } else if let Some((tile, _)) = tiles.iter().find(|(_, c)| *c == coords) {
+                        spawn_exploded_flag(&mut commands, tile, &board_assets, board.tile_size);
+                        life_lost_event_wr.send(LifeLostEvent {
+                            coordinates: *coords,
+                            remaining: board.lives.unwrap_or_default(),
+                        });
+                    }
+                }
+                if board.is_completed() {
+                    board_completed_event_wr.send(BoardCompletedEvent);
+                }
+            }
+        }
+        log::info!("Redone move {:?}", m.action);
+        board.history.push_redone(m);
+        board_change_ewr.send(BoardChangeEvent::Redone);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::resources::{GameStatus, MoveAction, TapMode};
+use crate::{Board, BoardAssets};
+use crate::{Hud, LivesText, MineCounterText, TapModeButton, TapModeText, TimerText};
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// Height of the HUD header, in logical pixels
+pub(crate) const HUD_HEIGHT: f32 = 50.;
+
+/// Is the window `position`, from the bottom left, under the HUD at the top of the window
+pub(crate) fn in_hud(window: &Window, position: Vec2) -> bool {
+    position.y > window.height() - HUD_HEIGHT
+}
+
+/// Tap mode toggle button colors
+const TAP_MODE_BUTTON: Color = Color::rgba(1., 1., 1., 0.15);
+const TAP_MODE_BUTTON_HOVERED: Color = Color::rgba(1., 1., 1., 0.3);
+
+/// Spawns the HUD header, the game status is inserted with the board
+pub fn spawn_hud(mut commands: Commands, board_assets: Res<BoardAssets>, tap_mode: Res<TapMode>) {
+    let style = TextStyle {
+        font: board_assets.bomb_counter_font.clone(),
+        font_size: HUD_HEIGHT / 2.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Px(HUD_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(0.),
+                    top: Val::Px(0.),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                padding: Rect::all(Val::Px(10.)),
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("HUD"))
+        .insert(Hud)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section("", style.clone(), Default::default()),
+                    ..Default::default()
+                })
+                .insert(Name::new("Mine Counter"))
+                .insert(MineCounterText);
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section("", style.clone(), Default::default()),
+                    ..Default::default()
+                })
+                .insert(Name::new("Lives"))
+                .insert(LivesText);
+            parent
+                // A plain node, so the menu buttons navigation and colors leave it alone
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        padding: Rect::all(Val::Px(5.)),
+                        ..Default::default()
+                    },
+                    color: TAP_MODE_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("Tap Mode"))
+                .insert(TapModeButton)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                tap_mode_label(*tap_mode),
+                                style.clone(),
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(TapModeText);
+                });
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section("", style, Default::default()),
+                    ..Default::default()
+                })
+                .insert(Name::new("Timer"))
+                .insert(TimerText);
+        });
+}
+
+/// Starts the timer on the first reveal and counts the playing time
+pub fn tick_timer(time: Res<Time>, board: Res<Board>, mut status: ResMut<GameStatus>) {
+    if !status.started
+        && board
+            .history
+            .moves()
+            .iter()
+            .any(|m| matches!(m.action, MoveAction::Reveal(_) | MoveAction::Chord(_)))
+    {
+        status.started = true;
+    }
+    if status.timer_running() {
+        status.elapsed += time.delta();
+    }
+}
+
+/// Updates the mine and lives counters and stops the timer when the game is over
+pub fn update_status(board: Res<Board>, mut status: ResMut<GameStatus>) {
+    let remaining_mines = board.remaining_mines();
+    if status.remaining_mines != remaining_mines {
+        status.remaining_mines = remaining_mines;
+    }
+    if status.lives != board.lives {
+        status.lives = board.lives;
+    }
+    // An undone fatal move resumes the game
+    let finished = board.is_completed() || board.is_lost();
+    if status.finished != finished {
+        status.finished = finished;
+    }
+}
+
+/// Displays the game status values in the HUD texts
+pub fn update_hud(
+    status: Res<GameStatus>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<MineCounterText>>,
+        QueryState<&mut Text, With<LivesText>>,
+        QueryState<&mut Text, With<TimerText>>,
+    )>,
+) {
+    if !status.is_changed() {
+        return;
+    }
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = format!("Mines: {}", status.remaining_mines);
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = match status.lives {
+            None => String::new(),
+            Some(lives) => format!("Lives: {}", lives),
+        };
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = format!("Time: {:03}", status.elapsed.as_secs());
+    }
+}
+
+/// Presses which started on the tap mode button
+#[derive(Debug, Default)]
+pub struct TapModePresses {
+    mouse: bool,
+    touches: HashSet<u64>,
+}
+
+/// Switches the tap mode when its HUD button is clicked or tapped.
+///
+/// The button is hit-tested against the cursor and touch positions, a press only counts when it
+/// starts and ends on the button
+pub fn toggle_tap_mode(
+    windows: Res<Windows>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut tap_mode: ResMut<TapMode>,
+    mut buttons: Query<(&Node, &GlobalTransform, &mut UiColor), With<TapModeButton>>,
+    mut touch_evr: EventReader<TouchInput>,
+    mut presses: Local<TapModePresses>,
+) {
+    let (node, transform, mut color) = match buttons.get_single_mut() {
+        Ok(b) => b,
+        Err(_) => return,
+    };
+    let on_button = |position: Vec2| contains(node, transform, position);
+    let hovered = windows
+        .get_primary()
+        .and_then(|w| w.cursor_position())
+        .is_some_and(on_button);
+    let mut toggle = false;
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        presses.mouse = hovered;
+    }
+    if mouse_buttons.just_released(MouseButton::Left) {
+        toggle |= presses.mouse && hovered;
+        presses.mouse = false;
+    }
+    for event in touch_evr.iter() {
+        match event.phase {
+            TouchPhase::Started if on_button(event.position) => {
+                presses.touches.insert(event.id);
+            }
+            TouchPhase::Started | TouchPhase::Moved => (),
+            TouchPhase::Ended => {
+                toggle |= presses.touches.remove(&event.id) && on_button(event.position);
+            }
+            TouchPhase::Cancelled => {
+                presses.touches.remove(&event.id);
+            }
+        }
+    }
+    if toggle {
+        *tap_mode = tap_mode.toggled();
+    }
+    let new_color = match hovered {
+        true => TAP_MODE_BUTTON_HOVERED,
+        false => TAP_MODE_BUTTON,
+    };
+    if color.0 != new_color {
+        color.0 = new_color;
+    }
+}
+
+/// Is the window `position` inside the UI `node`
+pub(crate) fn contains(node: &Node, transform: &GlobalTransform, position: Vec2) -> bool {
+    let center = transform.translation.truncate();
+    let half_size = node.size / 2.;
+    (position - center).abs().cmple(half_size).all()
+}
+
+/// Displays the tap mode in its HUD button
+pub fn update_tap_mode_label(
+    tap_mode: Res<TapMode>,
+    mut texts: Query<&mut Text, With<TapModeText>>,
+) {
+    if !tap_mode.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = tap_mode_label(*tap_mode);
+    }
+}
+
+fn tap_mode_label(tap_mode: TapMode) -> String {
+    format!("Tap: {}", tap_mode)
+}
+
+pub fn despawn_hud(mut commands: Commands, huds: Query<Entity, With<Hud>>) {
+    for entity in huds.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
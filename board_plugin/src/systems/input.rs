@@ -1,39 +1,211 @@
-use crate::events::TileTriggerEvent;
-use crate::Board;
+use crate::events::{SkipAnimations, TileChordEvent, TileTriggerEvent};
+use crate::resources::{InputAction, InputBinding, InputBindings, TilePointer};
+use crate::systems::hud;
 use crate::TileMarkEvent;
+use crate::{Board, BoardCamera, Coordinates};
 use bevy::input::{mouse::MouseButtonInput, ElementState};
 use bevy::log;
 use bevy::prelude::*;
 
+/// Mouse button held down over a tile
+#[derive(Debug, Copy, Clone)]
+pub struct TilePress {
+    button: MouseButton,
+    coordinates: Coordinates,
+    /// The reveal and flag buttons are both held, the press chords the tile
+    chord: bool,
+    /// Board the tile belongs to
+    board: Entity,
+}
+
+/// Reveals, flags or chords the tile under the mouse cursor, following the `InputBindings`.
+/// Holding the reveal and flag buttons together chords the tile, as in the classic game.
+///
+/// The action happens when the button is released over the tile it was pressed on, so a misclick
+/// is cancelled by moving away before releasing. The hovered and pressed tiles are set in the
+/// `TilePointer`
 pub fn input_handling(
     windows: Res<Windows>,
     board: Res<Board>,
+    input_bindings: Res<InputBindings>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    cameras: Query<(&Camera, &GlobalTransform, Option<&BoardCamera>)>,
+    mut pointer: ResMut<TilePointer>,
+    mut press: Local<Option<TilePress>>,
+    mut missing_camera: Local<bool>,
     mut button_evr: EventReader<MouseButtonInput>,
     mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
     mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
+    mut skip_animations_ewr: EventWriter<SkipAnimations>,
 ) {
     let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = match BoardCamera::picking_camera(cameras.iter()) {
+        Some(c) => c,
+        None => {
+            if !*missing_camera {
+                log::error!("No board camera nor single 2D camera to pick tiles with");
+                *missing_camera = true;
+            }
+            return;
+        }
+    };
+    // The HUD covers the top rows of the board, its clicks are not for the tiles below
+    let hovered = window
+        .cursor_position()
+        .filter(|pos| !hud::in_hud(window, *pos))
+        .and_then(|pos| board.mouse_position(window, camera, camera_transform, pos));
+    // A press on a previous board is dropped
+    if press.is_some_and(|p| p.board != board.entity) {
+        *press = None;
+    }
 
     for event in button_evr.iter() {
-        if let ElementState::Pressed = event.state {
-            let position = window.cursor_position();
-            if let Some(pos) = position {
-                log::trace!("Mouse button pressed: {:?} at {}", event.button, pos);
-                let tile_coordinates = board.mouse_position(window, pos);
-                if let Some(coordinates) = tile_coordinates {
-                    match event.button {
-                        MouseButton::Left => {
+        match event.state {
+            ElementState::Pressed => {
+                // Any click finishes the running animations
+                skip_animations_ewr.send(SkipAnimations);
+                log::trace!("Mouse button pressed: {:?} on {:?}", event.button, hovered);
+                match press.as_mut() {
+                    None => {
+                        *press = hovered.map(|coordinates| TilePress {
+                            button: event.button,
+                            coordinates,
+                            chord: false,
+                            board: board.entity,
+                        });
+                    }
+                    Some(p) if chord_buttons(&input_bindings, p.button, event.button) => {
+                        p.chord = true;
+                    }
+                    Some(_) => (),
+                }
+            }
+            ElementState::Released => {
+                let tile_press = match *press {
+                    Some(p) if p.button == event.button || p.chord => p,
+                    _ => continue,
+                };
+                *press = None;
+                if hovered != Some(tile_press.coordinates) {
+                    log::trace!("Mouse button released away from {}", tile_press.coordinates);
+                    continue;
+                }
+                let coordinates = tile_press.coordinates;
+                if tile_press.chord {
+                    log::info!("Trying to chord tile on {}", coordinates);
+                    tile_chord_ewr.send(TileChordEvent(coordinates));
+                    continue;
+                }
+                for action in input_bindings.actions(InputBinding::Mouse(event.button)) {
+                    match action {
+                        InputAction::Reveal => {
                             log::info!("Trying to uncover tile on {}", coordinates);
                             tile_trigger_ewr.send(TileTriggerEvent(coordinates));
                         }
-                        MouseButton::Right => {
+                        InputAction::Flag => {
                             log::info!("Trying to mark tile on {}", coordinates);
                             tile_mark_ewr.send(TileMarkEvent(coordinates));
                         }
+                        InputAction::Chord => {
+                            log::info!("Trying to chord tile on {}", coordinates);
+                            tile_chord_ewr.send(TileChordEvent(coordinates));
+                        }
                         _ => (),
                     }
                 }
             }
         }
     }
+
+    // So is a press released while the state was inactive
+    if press.is_some_and(|p| !mouse_buttons.pressed(p.button)) {
+        *press = None;
+    }
+
+    // The press preview is only shown while the cursor stays over the pressed tile
+    let pressed = match *press {
+        Some(p) if hovered == Some(p.coordinates) && p.chord => {
+            pressed_tiles(&board, [InputAction::Chord], p.coordinates)
+        }
+        Some(p) if hovered == Some(p.coordinates) => {
+            let actions = input_bindings.actions(InputBinding::Mouse(p.button));
+            pressed_tiles(&board, actions, p.coordinates)
+        }
+        _ => Vec::new(),
+    };
+    if pointer.hovered != hovered || pointer.pressed != pressed {
+        *pointer = TilePointer { hovered, pressed };
+    }
+}
+
+/// Are `first` and `second` the mouse buttons of a reveal and a flag, in any order
+fn chord_buttons(input_bindings: &InputBindings, first: MouseButton, second: MouseButton) -> bool {
+    let binds = |button, action| {
+        input_bindings
+            .actions(InputBinding::Mouse(button))
+            .any(|a| a == action)
+    };
+    (binds(first, InputAction::Reveal) && binds(second, InputAction::Flag))
+        || (binds(first, InputAction::Flag) && binds(second, InputAction::Reveal))
+}
+
+/// Covered and unmarked tiles the held mouse `actions` would act on: the tile itself for a
+/// reveal, and its 3x3 area for a chord
+fn pressed_tiles(
+    board: &Board,
+    actions: impl IntoIterator<Item = InputAction>,
+    coordinates: Coordinates,
+) -> Vec<Coordinates> {
+    let mut tiles = Vec::new();
+    for action in actions {
+        match action {
+            InputAction::Reveal => tiles.push(coordinates),
+            InputAction::Chord => {
+                tiles.push(coordinates);
+                tiles.extend(board.tile_map.safe_square_at(coordinates));
+            }
+            _ => (),
+        }
+    }
+    tiles.sort_by_key(|c| (c.y, c.x));
+    tiles.dedup();
+    tiles.retain(|c| board.tile_to_uncover(c).is_some());
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_and_flag_buttons_chord() {
+        let bindings = InputBindings::right_handed();
+        assert!(chord_buttons(
+            &bindings,
+            MouseButton::Left,
+            MouseButton::Right
+        ));
+        assert!(chord_buttons(
+            &bindings,
+            MouseButton::Right,
+            MouseButton::Left
+        ));
+        assert!(!chord_buttons(
+            &bindings,
+            MouseButton::Left,
+            MouseButton::Middle
+        ));
+        assert!(!chord_buttons(
+            &bindings,
+            MouseButton::Left,
+            MouseButton::Left
+        ));
+        let bindings = InputBindings::left_handed();
+        assert!(chord_buttons(
+            &bindings,
+            MouseButton::Right,
+            MouseButton::Left
+        ));
+    }
 }
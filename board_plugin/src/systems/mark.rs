@@ -1,4 +1,8 @@
-use crate::{Board, BoardAssets, TileMarkEvent};
+use crate::events::BoardChangeEvent;
+use crate::resources::MoveAction;
+use crate::resources::ThemedVisual;
+use crate::systems::animation::{flag_in_tween, flag_out_tween};
+use crate::{Board, BoardAssets, Coordinates, Themed, TileMarkEvent};
 use bevy::log;
 use bevy::prelude::*;
 
@@ -8,37 +12,93 @@ pub fn mark_tiles(
     board_assets: Res<BoardAssets>,
     mut tile_mark_event_rdr: EventReader<TileMarkEvent>,
     query: Query<&Children>,
+    mut board_change_ewr: EventWriter<BoardChangeEvent>,
 ) {
     for event in tile_mark_event_rdr.iter() {
         if let Some((entity, mark)) = board.try_toggle_mark(&event.0) {
-            if mark {
-                commands.entity(entity).with_children(|parent| {
-                    parent
-                        .spawn_bundle(SpriteBundle {
-                            texture: board_assets.flag_material.texture.clone(),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(board.tile_size)),
-                                color: board_assets.flag_material.color,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(0., 0., 1.),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Flag"));
-                });
-            } else {
-                // TODO: have a better optimized query
-                let children = match query.get(entity) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        log::error!("Failed to retrieve flag entity components: {}", e);
-                        continue;
-                    }
-                };
-                for child in children.iter() {
-                    commands.entity(*child).despawn_recursive();
-                }
+            let action = MoveAction::ToggleMark(event.0);
+            board.history.push(action);
+            board_change_ewr.send(BoardChangeEvent::MoveStarted(action));
+            update_flag(
+                &mut commands,
+                entity,
+                mark,
+                board.tile_size,
+                &board_assets,
+                &query,
+            );
+        }
+    }
+}
+
+/// Spawns or despawns the flag of a tile cover `entity` according to `mark`
+pub(crate) fn update_flag(
+    commands: &mut Commands,
+    entity: Entity,
+    mark: bool,
+    size: f32,
+    board_assets: &BoardAssets,
+    query: &Query<&Children>,
+) {
+    if mark {
+        commands.entity(entity).with_children(|parent| {
+            let mut flag = parent.spawn();
+            board_assets.insert_flag(&mut flag, Vec2::splat(size));
+            flag.insert(flag_in_tween());
+        });
+    } else {
+        // TODO: have a better optimized query
+        let children = match query.get(entity) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to retrieve flag entity components: {}", e);
+                return;
             }
+        };
+        for child in children.iter() {
+            commands.entity(*child).insert(flag_out_tween());
+        }
+    }
+}
+
+/// Shows the flags of tiles without bomb as wrong once the game is lost, and as flags again if
+/// the loss is undone
+pub fn update_wrong_flags(
+    mut commands: Commands,
+    board: Res<Board>,
+    board_assets: Res<BoardAssets>,
+    flags: Query<(Entity, &Themed, &Parent)>,
+    parents: Query<&Parent>,
+    tiles: Query<&Coordinates>,
+) {
+    if !board.is_changed() {
+        return;
+    }
+    let lost = board.is_lost();
+    for (entity, themed, cover) in flags.iter() {
+        let shown_wrong = match themed {
+            Themed::Flag => false,
+            Themed::WrongFlag => true,
+            _ => continue,
+        };
+        // Flags are children of the tile covers
+        let bomb = match parents.get(cover.0).and_then(|tile| tiles.get(tile.0)) {
+            Ok(coordinates) => board.tile_map.is_bomb_at(*coordinates),
+            Err(_) => continue,
+        };
+        let wrong = lost && !bomb;
+        if wrong == shown_wrong {
+            continue;
         }
+        let themed = match wrong {
+            true => Themed::WrongFlag,
+            false => Themed::Flag,
+        };
+        let mut entity = commands.entity(entity);
+        ThemedVisual::remove(&mut entity);
+        entity.insert(themed);
+        board_assets
+            .visual(themed, Vec2::splat(board.tile_size))
+            .insert(&mut entity);
     }
-}
\ No newline at end of file
+}
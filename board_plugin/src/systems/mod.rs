@@ -1,3 +1,17 @@
+pub mod animation;
+pub mod camera;
+pub mod cursor;
+pub mod gamepad;
+pub mod highlight;
+pub mod history;
+pub mod hud;
 pub mod input;
 pub mod mark;
+pub mod particles;
+pub mod playback;
+pub mod replay;
+pub mod theme;
+pub mod touch;
 pub mod uncover;
+#[cfg(target_arch = "wasm32")]
+pub mod web_touch;
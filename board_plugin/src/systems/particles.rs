@@ -0,0 +1,143 @@
+use crate::events::{BoardCompletedEvent, BombExplosionEvent, LifeLostEvent};
+use crate::resources::ParticleEmitter;
+use crate::{Board, BoardAssets, BoardOptions, Coordinates, Particle};
+use bevy::prelude::*;
+use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use std::f32::consts::TAU;
+
+/// Particles are drawn above every tile entity
+const PARTICLE_Z: f32 = 10.;
+
+/// Throws debris and smoke from the exploded tiles
+pub fn explosion_particles(
+    mut commands: Commands,
+    board: Res<Board>,
+    board_assets: Res<BoardAssets>,
+    board_options: Option<Res<BoardOptions>>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut life_lost_evr: EventReader<LifeLostEvent>,
+) {
+    let exploded: Vec<Coordinates> = bomb_explosion_evr
+        .iter()
+        .map(|e| e.0)
+        .chain(life_lost_evr.iter().map(|e| e.coordinates))
+        .collect();
+    if exploded.is_empty() || board_options.is_some_and(|o| !o.animations) {
+        return;
+    }
+    let particles = &board_assets.particles;
+    let size = board.tile_size;
+    commands.entity(board.entity).with_children(|parent| {
+        for coords in exploded {
+            let center = Vec2::new(
+                (coords.x as f32 + 0.5) * size,
+                (coords.y as f32 + 0.5) * size,
+            );
+            // Debris are thrown in every direction
+            emit(parent, &particles.debris, size, |rng| {
+                let angle = rng.gen_range(0.0..TAU);
+                (center, Vec2::new(angle.cos(), angle.sin()))
+            });
+            // Smoke goes up
+            emit(parent, &particles.smoke, size, |rng| {
+                let offset = Vec2::new(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3));
+                let direction = Vec2::new(rng.gen_range(-0.5..0.5), 1.).normalize();
+                (center + offset * size, direction)
+            });
+        }
+    });
+}
+
+/// Throws confetti over the board when it is completed
+pub fn victory_confetti(
+    mut commands: Commands,
+    board: Res<Board>,
+    board_assets: Res<BoardAssets>,
+    board_options: Option<Res<BoardOptions>>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+) {
+    if board_completed_evr.iter().count() == 0 || board_options.is_some_and(|o| !o.animations) {
+        return;
+    }
+    let board_size = board.bounds.size;
+    commands.entity(board.entity).with_children(|parent| {
+        // Confetti are thrown up from the upper half of the board and fall across it
+        emit(
+            parent,
+            &board_assets.particles.confetti,
+            board.tile_size,
+            |rng| {
+                let position = Vec2::new(
+                    rng.gen_range(0.0..=board_size.x),
+                    rng.gen_range(board_size.y / 2.0..=board_size.y),
+                );
+                let direction = Vec2::new(rng.gen_range(-0.5..0.5), 1.).normalize();
+                (position, direction)
+            },
+        );
+    });
+}
+
+/// Spawns the particles of an `emitter`, `origin` giving each particle position and direction
+fn emit(
+    parent: &mut ChildBuilder,
+    emitter: &ParticleEmitter,
+    tile_size: f32,
+    mut origin: impl FnMut(&mut ThreadRng) -> (Vec2, Vec2),
+) {
+    let mut rng = thread_rng();
+    for _ in 0..emitter.count {
+        let (position, direction) = origin(&mut rng);
+        let color = emitter
+            .colors
+            .choose(&mut rng)
+            .copied()
+            .unwrap_or(Color::WHITE);
+        let speed = emitter.speed * tile_size * rng.gen_range(0.5..=1.);
+        parent
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(emitter.size * tile_size)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(PARTICLE_Z)),
+                ..Default::default()
+            })
+            .insert(Name::new("Particle"))
+            .insert(Particle {
+                velocity: direction * speed,
+                gravity: emitter.gravity * tile_size,
+                spin: rng.gen_range(-1.0..=1.) * emitter.spin,
+                growth: emitter.growth,
+                lifetime: emitter.lifetime * rng.gen_range(0.5..=1.),
+                age: 0.,
+                alpha: color.a(),
+            });
+    }
+}
+
+/// Moves, spins, grows and fades the particles, despawning the expired ones
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = particle.age / particle.lifetime;
+        particle.velocity.y -= particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.);
+        transform.rotate(Quat::from_rotation_z(particle.spin * delta));
+        let scale = 1. + (particle.growth - 1.) * progress;
+        transform.scale = Vec3::new(scale, scale, 1.);
+        sprite.color.set_a(particle.alpha * (1. - progress));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::events::{RedoEvent, ResetBoard, SkipAnimations, TileChordEvent, UndoEvent};
+use crate::resources::{GameStatus, Playback, ReplayAction};
+use crate::systems::cursor::cursor_transform;
+use crate::{Board, BoardAssets, Coordinates, CursorPathDot, Themed, TileCursor, Uncover};
+use crate::{TileMarkEvent, TileTriggerEvent};
+use bevy::ecs::schedule::SystemLabel;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Number of pointed tiles drawn in the cursor path
+const PATH_LENGTH: usize = 20;
+/// The cursor path is drawn below the cursor, above the covers
+const PATH_Z: f32 = 4.;
+
+/// Label of the replay playback, which sends the tile events before the board handles them
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct PlaybackDriver;
+
+/// Plays the `Playback` replay back by sending its recorded events at their time.
+///
+/// An event changing the board waits for the previous uncovering to finish. A backwards seek
+/// rebuilds the board then catches up without animations
+pub fn play_replay(
+    time: Res<Time>,
+    board: Res<Board>,
+    mut playback: ResMut<Playback>,
+    mut status: ResMut<GameStatus>,
+    uncovering: Query<(), With<Uncover>>,
+    mut rebuilt_board: Local<Option<Entity>>,
+    mut reset_board_ewr: EventWriter<ResetBoard>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
+    mut undo_ewr: EventWriter<UndoEvent>,
+    mut redo_ewr: EventWriter<RedoEvent>,
+    mut skip_animations_ewr: EventWriter<SkipAnimations>,
+) {
+    playback.tick(time.delta());
+    // The game timer starts with the first reveal
+    let elapsed = match playback.start_time() {
+        Some(start) if start <= playback.time() => playback.time() - start,
+        _ => Duration::ZERO,
+    };
+    if status.elapsed != elapsed {
+        status.elapsed = elapsed;
+    }
+
+    if playback.take_rebuild() {
+        *rebuilt_board = Some(board.entity);
+        reset_board_ewr.send(ResetBoard { same_layout: true });
+        return;
+    }
+    match *rebuilt_board {
+        Some(entity) if entity == board.entity => return,
+        Some(_) => *rebuilt_board = None,
+        None => (),
+    }
+    if !uncovering.is_empty() {
+        return;
+    }
+
+    let mut marked = false;
+    while let Some(action) = playback.peek_due_action() {
+        let changes_board = !matches!(action, ReplayAction::Cursor(_) | ReplayAction::Flag(_));
+        // Marks and reveals are handled by different systems, in any order
+        if changes_board && marked {
+            break;
+        }
+        playback.next_due_event();
+        match action {
+            ReplayAction::Reveal(coordinates) => {
+                tile_trigger_ewr.send(TileTriggerEvent(coordinates))
+            }
+            ReplayAction::Flag(coordinates) => {
+                tile_mark_ewr.send(TileMarkEvent(coordinates));
+                marked = true;
+            }
+            ReplayAction::Chord(coordinates) => tile_chord_ewr.send(TileChordEvent(coordinates)),
+            ReplayAction::Undo => undo_ewr.send(UndoEvent),
+            ReplayAction::Redo => redo_ewr.send(RedoEvent),
+            ReplayAction::Cursor(_) => (),
+        }
+        if changes_board {
+            break;
+        }
+    }
+    // Late events are caught up as fast as the board allows
+    if playback.peek_due_action().is_some() {
+        skip_animations_ewr.send(SkipAnimations);
+    }
+}
+
+/// Draws the last tiles pointed in the `Playback` replay, the tile cursor showing the current one
+pub fn draw_cursor_path(
+    mut commands: Commands,
+    board: Res<Board>,
+    board_assets: Res<BoardAssets>,
+    playback: Res<Playback>,
+    mut drawn: Local<(Option<Entity>, Vec<Coordinates>)>,
+    dots: Query<Entity, With<CursorPathDot>>,
+    mut cursors: Query<(&mut TileCursor, &mut Transform, &mut Visibility)>,
+) {
+    let mut path: Vec<Coordinates> = playback.cursor_path().rev().take(PATH_LENGTH).collect();
+    path.reverse();
+    if drawn.0 == Some(board.entity) && drawn.1 == path {
+        return;
+    }
+    *drawn = (Some(board.entity), path.clone());
+
+    for (mut cursor, mut transform, mut visibility) in cursors.iter_mut() {
+        visibility.is_visible = !path.is_empty();
+        if let Some(coordinates) = path.last() {
+            cursor.coordinates = *coordinates;
+            *transform = cursor_transform(*coordinates, board.tile_size);
+        }
+    }
+    for entity in dots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let size = Vec2::splat(board.tile_size / 4.);
+    commands.entity(board.entity).with_children(|parent| {
+        for coordinates in path {
+            let mut transform = cursor_transform(coordinates, board.tile_size);
+            transform.translation.z = PATH_Z;
+            let mut dot = parent.spawn();
+            board_assets.insert_themed(&mut dot, Themed::Hover, size, transform);
+            dot.insert(Name::new("Cursor Path Dot"))
+                .insert(CursorPathDot);
+        }
+    });
+}
@@ -0,0 +1,51 @@
+use crate::events::{RedoEvent, TileChordEvent, TileMarkEvent, TileTriggerEvent, UndoEvent};
+use crate::resources::{Replay, ReplayAction, TilePointer};
+use crate::{Coordinates, TileCursor};
+use bevy::prelude::*;
+
+/// Records the player actions and the pointed tile in the `Replay`
+pub fn record_replay(
+    time: Res<Time>,
+    replay: Option<ResMut<Replay>>,
+    pointer: Res<TilePointer>,
+    cursors: Query<(&TileCursor, &Visibility)>,
+    mut pointed: Local<Option<Coordinates>>,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+    mut tile_chord_evr: EventReader<TileChordEvent>,
+    mut undo_evr: EventReader<UndoEvent>,
+    mut redo_evr: EventReader<RedoEvent>,
+) {
+    let mut replay = match replay {
+        Some(r) => r,
+        None => return,
+    };
+    replay.duration += time.delta();
+
+    // The visible keyboard cursor takes over the mouse
+    let cursor = cursors
+        .iter()
+        .find(|(_, visibility)| visibility.is_visible)
+        .map(|(cursor, _)| cursor.coordinates);
+    let current = cursor.or(pointer.hovered);
+    if *pointed != current {
+        *pointed = current;
+        replay.record(ReplayAction::Cursor(current));
+    }
+
+    for event in tile_trigger_evr.iter() {
+        replay.record(ReplayAction::Reveal(event.0));
+    }
+    for event in tile_mark_evr.iter() {
+        replay.record(ReplayAction::Flag(event.0));
+    }
+    for event in tile_chord_evr.iter() {
+        replay.record(ReplayAction::Chord(event.0));
+    }
+    for _ in undo_evr.iter() {
+        replay.record(ReplayAction::Undo);
+    }
+    for _ in redo_evr.iter() {
+        replay.record(ReplayAction::Redo);
+    }
+}
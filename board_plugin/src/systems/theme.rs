@@ -0,0 +1,104 @@
+use crate::resources::{ActiveTheme, Board, BoardAssets, BoardTheme, ThemedVisual};
+use crate::Themed;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::render::render_resource::FilterMode;
+
+/// Copies the active theme into `BoardAssets` when it is selected, loaded or hot reloaded
+pub fn apply_theme(
+    active_theme: Option<Res<ActiveTheme>>,
+    themes: Res<Assets<BoardTheme>>,
+    mut theme_evr: EventReader<AssetEvent<BoardTheme>>,
+    mut board_assets: ResMut<BoardAssets>,
+) {
+    let active_theme = match active_theme {
+        None => return,
+        Some(t) => t,
+    };
+    let reloaded = theme_evr.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            *handle == active_theme.0
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    if !reloaded && !active_theme.is_changed() {
+        return;
+    }
+    // A newly selected theme may still be loading, it is applied on its `Created` event
+    if let Some(theme) = themes.get(&active_theme.0) {
+        log::info!("Applying board theme {}", theme.assets.label);
+        *board_assets = theme.assets.clone();
+    }
+}
+
+/// Applies `BoardAssets` changes to the spawned board entities, keeping the game state
+pub fn restyle_board(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    themed: Query<(Entity, &Themed)>,
+) {
+    if !board_assets.is_changed() || board_assets.is_added() {
+        return;
+    }
+    if let Some(board) = board {
+        restyle(&mut commands, &board, &board_assets, &themed);
+    }
+}
+
+/// Replaces the visual components of the `themed` entities, sized from the board layout
+pub(crate) fn restyle(
+    commands: &mut Commands,
+    board: &Board,
+    board_assets: &BoardAssets,
+    themed: &Query<(Entity, &Themed)>,
+) {
+    let tile_size = Vec2::splat(board.tile_size - board.tile_padding);
+    for (entity, themed) in themed.iter() {
+        let size = match themed {
+            Themed::Board => board.bounds.size,
+            Themed::Flag
+            | Themed::WrongFlag
+            | Themed::Exploded
+            | Themed::Cursor
+            | Themed::Hover => Vec2::splat(board.tile_size),
+            _ => tile_size,
+        };
+        // The visual components may change kind, from a sprite to an atlas frame for example
+        let mut entity = commands.entity(entity);
+        ThemedVisual::remove(&mut entity);
+        board_assets.visual(*themed, size).insert(&mut entity);
+    }
+}
+
+/// Switches pixelated atlas textures to nearest filtering once they are loaded
+pub fn pixelate_atlas(
+    board_assets: Res<BoardAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut image_evr: EventReader<AssetEvent<Image>>,
+) {
+    let atlas = match &board_assets.tile_atlas {
+        Some(a) if a.pixelated => a,
+        _ => return,
+    };
+    let loaded = image_evr.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            *handle == atlas.texture
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    if !loaded && !board_assets.is_changed() {
+        return;
+    }
+    let nearest = |image: &Image| {
+        let sampler = &image.sampler_descriptor;
+        sampler.mag_filter == FilterMode::Nearest && sampler.min_filter == FilterMode::Nearest
+    };
+    // Checking first avoids a modification loop, since `get_mut` sends a `Modified` event
+    if images.get(&atlas.texture).is_some_and(|i| !nearest(i)) {
+        if let Some(image) = images.get_mut(&atlas.texture) {
+            image.sampler_descriptor.mag_filter = FilterMode::Nearest;
+            image.sampler_descriptor.min_filter = FilterMode::Nearest;
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use crate::events::{SkipAnimations, TileMarkEvent, TileTriggerEvent};
+use crate::resources::TapMode;
+use crate::systems::hud;
+use crate::{Board, BoardCamera, BoardOptions};
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Window distance a touch may move and still count as a tap, in logical pixels
+const TAP_SLOP: f32 = 10.;
+/// Long press delay used when no board options are set
+const DEFAULT_LONG_PRESS_DELAY: f32 = 0.5;
+
+/// Single finger touch which may still become a tap or a long press
+#[derive(Debug, Copy, Clone)]
+struct PendingTap {
+    id: u64,
+    start: Vec2,
+    held: f32,
+    long_pressed: bool,
+}
+
+impl PendingTap {
+    /// Holds the touch for `delta` more seconds, returning `true` once it becomes a long press
+    fn hold(&mut self, delta: f32, long_press_delay: f32) -> bool {
+        if self.long_pressed {
+            return false;
+        }
+        self.held += delta;
+        self.long_pressed = self.held >= long_press_delay;
+        self.long_pressed
+    }
+}
+
+/// Touches tracked across frames by `touch_input`
+#[derive(Debug, Default)]
+pub struct TouchTracker {
+    /// Current position of every finger on the screen
+    active: HashMap<u64, Vec2>,
+    tap: Option<PendingTap>,
+}
+
+/// Reveals or flags tiles with taps and long presses, depending on the `TapMode`.
+///
+/// Multi-finger gestures are left to the camera. The system only reads `TouchInput` events and
+/// `Time`, so touches can be injected by sending the events. Winit sends them on Android and iOS,
+/// the web canvas touches are sent by `web_touch`
+pub fn touch_input(
+    windows: Res<Windows>,
+    time: Res<Time>,
+    board: Res<Board>,
+    board_options: Option<Res<BoardOptions>>,
+    tap_mode: Res<TapMode>,
+    cameras: Query<(&Camera, &GlobalTransform, Option<&BoardCamera>)>,
+    mut touch_evr: EventReader<TouchInput>,
+    mut tracker: Local<TouchTracker>,
+    mut missing_camera: Local<bool>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut skip_animations_ewr: EventWriter<SkipAnimations>,
+) {
+    let window = match windows.get_primary() {
+        Some(w) => w,
+        None => return,
+    };
+    let (camera, camera_transform) = match BoardCamera::picking_camera(cameras.iter()) {
+        Some(c) => c,
+        None => {
+            if !*missing_camera {
+                log::error!("No board camera nor single 2D camera to pick tiles with");
+                *missing_camera = true;
+            }
+            return;
+        }
+    };
+    let long_press_delay = board_options
+        .map(|o| o.long_press_delay)
+        .unwrap_or(DEFAULT_LONG_PRESS_DELAY);
+    let mut act = |mode: TapMode, position: Vec2| {
+        let coordinates = match board.mouse_position(window, camera, camera_transform, position) {
+            Some(c) => c,
+            None => return,
+        };
+        match mode {
+            TapMode::Reveal => {
+                log::info!("Trying to uncover tile on {}", coordinates);
+                tile_trigger_ewr.send(TileTriggerEvent(coordinates));
+            }
+            TapMode::Flag => {
+                log::info!("Trying to mark tile on {}", coordinates);
+                tile_mark_ewr.send(TileMarkEvent(coordinates));
+            }
+        }
+    };
+
+    for event in touch_evr.iter() {
+        match event.phase {
+            TouchPhase::Started => {
+                tracker.active.insert(event.id, event.position);
+                // Any touch finishes the running animations
+                skip_animations_ewr.send(SkipAnimations);
+                // A second finger turns the touch into a camera gesture
+                tracker.tap = (tracker.active.len() == 1).then_some(PendingTap {
+                    id: event.id,
+                    start: event.position,
+                    held: 0.,
+                    long_pressed: false,
+                });
+            }
+            TouchPhase::Moved => {
+                tracker.active.insert(event.id, event.position);
+                if tracker.tap.is_some_and(|tap| {
+                    tap.id == event.id && tap.start.distance(event.position) > TAP_SLOP
+                }) {
+                    tracker.tap = None;
+                }
+            }
+            TouchPhase::Ended => {
+                tracker.active.remove(&event.id);
+                let tap = match tracker.tap {
+                    Some(tap) if tap.id == event.id => tap,
+                    _ => continue,
+                };
+                tracker.tap = None;
+                if tap.long_pressed {
+                    continue;
+                }
+                // The HUD covers the top of the window, its taps are left to `toggle_tap_mode`
+                if hud::in_hud(window, event.position) {
+                    continue;
+                }
+                log::trace!("Tap at {}", event.position);
+                act(*tap_mode, event.position);
+            }
+            TouchPhase::Cancelled => {
+                tracker.active.remove(&event.id);
+                if tracker.tap.is_some_and(|tap| tap.id == event.id) {
+                    tracker.tap = None;
+                }
+            }
+        }
+    }
+
+    // A touch held long enough does the other action, without waiting for its release
+    if let Some(tap) = tracker.tap.as_mut() {
+        if !hud::in_hud(window, tap.start) && tap.hold(time.delta_seconds(), long_press_delay) {
+            log::trace!("Long press at {}", tap.start);
+            act(tap_mode.toggled(), tap.start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::hud;
+    use crate::{Coordinates, TapModeButton, TileMap};
+    use bevy::app::Events;
+    use bevy::ecs::event::ManualEventReader;
+    use bevy::math::DVec2;
+    use bevy::window::WindowId;
+    use raw_window_handle::{RawWindowHandle, WebHandle};
+
+    /// Long press delay of the tests
+    const LONG_PRESS_DELAY: f32 = 0.2;
+
+    fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    /// Window position of the center of the tile at `x` and `y`
+    fn tile(x: u16, y: u16) -> Vec2 {
+        Vec2::new((x as f32 + 0.5) * 50., (y as f32 + 0.5) * 50.)
+    }
+
+    /// Window position of the center of the tap mode button, in the HUD
+    fn button() -> Vec2 {
+        Vec2::new(170., 175.)
+    }
+
+    /// App running the touch systems, with a 200x200 window showing a 4x4 board of 50 pixels
+    /// tiles. The revealed and flagged tiles are read from the tile events.
+    ///
+    /// `Time` is never updated so the frames take no time, the long press timing is tested on
+    /// `PendingTap` itself
+    struct TouchApp {
+        app: App,
+        triggers: ManualEventReader<TileTriggerEvent>,
+        marks: ManualEventReader<TileMarkEvent>,
+    }
+
+    impl TouchApp {
+        fn new() -> Self {
+            Self::with_long_press_delay(LONG_PRESS_DELAY)
+        }
+
+        fn with_long_press_delay(long_press_delay: f32) -> Self {
+            let mut windows = Windows::default();
+            windows.add(Window::new(
+                WindowId::primary(),
+                &WindowDescriptor::default(),
+                200,
+                200,
+                1.,
+                None,
+                RawWindowHandle::Web(WebHandle::empty()),
+            ));
+            let tile_map = TileMap::with_bombs(4, 4, [c(0, 0)]).unwrap();
+            let mut app = App::new();
+            app.insert_resource(windows)
+                .insert_resource(Board::covered(tile_map))
+                .insert_resource(BoardOptions {
+                    long_press_delay,
+                    ..Default::default()
+                })
+                .init_resource::<Time>()
+                .init_resource::<TapMode>()
+                .init_resource::<Input<MouseButton>>()
+                .add_event::<TouchInput>()
+                .add_event::<TileTriggerEvent>()
+                .add_event::<TileMarkEvent>()
+                .add_event::<SkipAnimations>()
+                .add_system(touch_input)
+                .add_system(hud::toggle_tap_mode);
+            app.world
+                .spawn()
+                .insert(Camera {
+                    projection_matrix: Mat4::orthographic_rh(0., 4., 0., 4., -1., 1.),
+                    ..Default::default()
+                })
+                .insert(GlobalTransform::identity())
+                .insert(BoardCamera);
+            app.world
+                .spawn()
+                .insert(Node {
+                    size: Vec2::new(40., 30.),
+                })
+                .insert(GlobalTransform::from_xyz(170., 175., 0.))
+                .insert(UiColor::default())
+                .insert(TapModeButton);
+            Self {
+                app,
+                triggers: Default::default(),
+                marks: Default::default(),
+            }
+        }
+
+        fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2) {
+            let mut events = self
+                .app
+                .world
+                .get_resource_mut::<Events<TouchInput>>()
+                .unwrap();
+            events.send(TouchInput {
+                phase,
+                position,
+                force: None,
+                id,
+            });
+        }
+
+        /// Runs a frame, returning the revealed and the flagged tiles
+        fn update(&mut self) -> (Vec<Coordinates>, Vec<Coordinates>) {
+            self.app.update();
+            let world = &self.app.world;
+            let triggers = world.get_resource::<Events<TileTriggerEvent>>().unwrap();
+            let marks = world.get_resource::<Events<TileMarkEvent>>().unwrap();
+            (
+                self.triggers.iter(triggers).map(|e| e.0).collect(),
+                self.marks.iter(marks).map(|e| e.0).collect(),
+            )
+        }
+
+        fn tap_mode(&self) -> TapMode {
+            *self.app.world.get_resource::<TapMode>().unwrap()
+        }
+    }
+
+    #[test]
+    fn tap_reveals_tile() {
+        let mut app = TouchApp::new();
+        app.touch(1, TouchPhase::Started, tile(1, 2));
+        app.touch(1, TouchPhase::Ended, tile(1, 2));
+        assert_eq!(app.update(), (vec![c(1, 2)], vec![]));
+    }
+
+    #[test]
+    fn long_press_flags_tile() {
+        let mut app = TouchApp::with_long_press_delay(0.);
+        app.touch(1, TouchPhase::Started, tile(2, 1));
+        // The flag is set without waiting for the release
+        assert_eq!(app.update(), (vec![], vec![c(2, 1)]));
+        assert_eq!(app.update(), (vec![], vec![]));
+        app.touch(1, TouchPhase::Ended, tile(2, 1));
+        assert_eq!(app.update(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn touch_moving_past_the_slop_is_no_tap() {
+        let mut app = TouchApp::with_long_press_delay(0.);
+        // A small move still taps
+        let nudged = tile(1, 1) + Vec2::new(TAP_SLOP / 2., 0.);
+        app.touch(1, TouchPhase::Started, tile(1, 1));
+        app.touch(1, TouchPhase::Moved, nudged);
+        app.touch(1, TouchPhase::Ended, nudged);
+        assert_eq!(app.update(), (vec![c(1, 1)], vec![]));
+
+        let dragged = tile(1, 1) + Vec2::new(0., TAP_SLOP * 2.);
+        app.touch(2, TouchPhase::Started, tile(1, 1));
+        app.touch(2, TouchPhase::Moved, dragged);
+        // Neither a long press
+        assert_eq!(app.update(), (vec![], vec![]));
+        assert_eq!(app.update(), (vec![], vec![]));
+        app.touch(2, TouchPhase::Ended, dragged);
+        assert_eq!(app.update(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn second_finger_is_no_tap() {
+        let mut app = TouchApp::new();
+        app.touch(1, TouchPhase::Started, tile(1, 1));
+        app.touch(2, TouchPhase::Started, tile(2, 2));
+        app.touch(1, TouchPhase::Ended, tile(1, 1));
+        app.touch(2, TouchPhase::Ended, tile(2, 2));
+        assert_eq!(app.update(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn button_tap_toggles_tap_mode() {
+        let mut app = TouchApp::new();
+        app.touch(1, TouchPhase::Started, button());
+        app.touch(1, TouchPhase::Ended, button());
+        assert_eq!(app.update(), (vec![], vec![]));
+        assert_eq!(app.tap_mode(), TapMode::Flag);
+        // Taps flag tiles in the flag mode
+        app.touch(2, TouchPhase::Started, tile(3, 0));
+        app.touch(2, TouchPhase::Ended, tile(3, 0));
+        assert_eq!(app.update(), (vec![], vec![c(3, 0)]));
+        // A touch ending on the button without starting on it does not toggle
+        app.touch(3, TouchPhase::Started, tile(3, 2));
+        app.touch(3, TouchPhase::Moved, button());
+        app.touch(3, TouchPhase::Ended, button());
+        assert_eq!(app.update(), (vec![], vec![]));
+        assert_eq!(app.tap_mode(), TapMode::Flag);
+    }
+
+    #[test]
+    fn button_click_toggles_tap_mode() {
+        let mut app = TouchApp::new();
+        let world = &mut app.app.world;
+        let mut windows = world.get_resource_mut::<Windows>().unwrap();
+        let position = Some(DVec2::new(button().x as f64, button().y as f64));
+        let window = windows.get_primary_mut().unwrap();
+        window.update_cursor_physical_position_from_backend(position);
+        let mut mouse_buttons = world.get_resource_mut::<Input<MouseButton>>().unwrap();
+        mouse_buttons.press(MouseButton::Left);
+        app.update();
+        assert_eq!(app.tap_mode(), TapMode::Reveal);
+        let world = &mut app.app.world;
+        let mut mouse_buttons = world.get_resource_mut::<Input<MouseButton>>().unwrap();
+        mouse_buttons.clear();
+        mouse_buttons.release(MouseButton::Left);
+        app.update();
+        assert_eq!(app.tap_mode(), TapMode::Flag);
+    }
+
+    #[test]
+    fn tap_becomes_long_press_after_the_delay() {
+        let mut tap = PendingTap {
+            id: 1,
+            start: tile(1, 1),
+            held: 0.,
+            long_pressed: false,
+        };
+        assert!(!tap.hold(0.1, LONG_PRESS_DELAY));
+        assert!(!tap.hold(0.05, LONG_PRESS_DELAY));
+        assert!(tap.hold(0.1, LONG_PRESS_DELAY));
+        // The long press only fires once
+        assert!(!tap.hold(1., LONG_PRESS_DELAY));
+        assert!(tap.long_pressed);
+    }
+
+    #[test]
+    fn held_touch_waits_for_the_long_press_delay() {
+        let mut app = TouchApp::new();
+        app.touch(1, TouchPhase::Started, tile(2, 1));
+        assert_eq!(app.update(), (vec![], vec![]));
+        assert_eq!(app.update(), (vec![], vec![]));
+        app.touch(1, TouchPhase::Ended, tile(2, 1));
+        assert_eq!(app.update(), (vec![c(2, 1)], vec![]));
+    }
+}
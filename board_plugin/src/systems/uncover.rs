@@ -1,33 +1,69 @@
-use crate::events::TileTriggerEvent;
-use crate::{Board, Bomb, BombNeighbor, Coordinates, Uncover};
-use crate::{BoardCompletedEvent, BombExplosionEvent};
+use crate::events::{BoardChangeEvent, TileChordEvent, TileTriggerEvent};
+use crate::resources::MoveAction;
+use crate::systems::animation::reveal_cover;
+use crate::RevealDepth;
+use crate::{Board, BoardAssets, Bomb, BombNeighbor, Coordinates, Uncover};
+use crate::{BoardCompletedEvent, BombExplosionEvent, LifeLostEvent};
 use bevy::log;
 use bevy::prelude::*;
 
+/// Starts uncovering the triggered tiles, a chord is a single move uncovering all its tiles
 pub fn trigger_event_handler(
     mut commands: Commands,
-    board: Res<Board>,
+    mut board: ResMut<Board>,
     mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tile_chord_evr: EventReader<TileChordEvent>,
+    mut board_change_ewr: EventWriter<BoardChangeEvent>,
 ) {
-    for trigger_event in tile_trigger_evr.iter() {
-        if let Some(entity) = board.tile_to_uncover(&trigger_event.0) {
-            commands.entity(*entity).insert(Uncover {});
+    let triggers = tile_trigger_evr
+        .iter()
+        .map(|e| (MoveAction::Reveal(e.0), vec![e.0]));
+    let chords: Vec<(MoveAction, Vec<Coordinates>)> = tile_chord_evr
+        .iter()
+        .map(|e| (MoveAction::Chord(e.0), board.chord_tiles(e.0)))
+        .collect();
+    for (action, tiles) in triggers.chain(chords) {
+        let entities: Vec<Entity> = tiles
+            .iter()
+            .filter_map(|c| board.tile_to_uncover(c))
+            .copied()
+            .collect();
+        if entities.is_empty() {
+            continue;
+        }
+        board.history.push(action);
+        let move_id = board.history.current_id();
+        for entity in entities {
+            commands.entity(entity).insert(Uncover { move_id });
         }
+        board_change_ewr.send(BoardChangeEvent::MoveStarted(action));
     }
 }
 
 pub fn uncover_tiles(
     mut commands: Commands,
     mut board: ResMut<Board>,
-    children: Query<(Entity, &Parent), With<Uncover>>,
+    board_assets: Res<BoardAssets>,
+    children: Query<(Entity, &Parent, &Uncover, Option<&RevealDepth>)>,
     parents: Query<(&Coordinates, Option<&Bomb>, Option<&BombNeighbor>)>,
     mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
     mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+    mut life_lost_event_wr: EventWriter<LifeLostEvent>,
+    mut board_change_ewr: EventWriter<BoardChangeEvent>,
 ) {
     // We iterate through tile covers to uncover
-    for (entity, parent) in children.iter() {
-        // we destroy the tile cover entity
-        commands.entity(entity).despawn_recursive();
+    for (entity, parent, uncover, depth) in children.iter() {
+        // The cascade of an undone move stops
+        if uncover
+            .move_id
+            .is_some_and(|id| !board.history.is_played(id))
+        {
+            commands.entity(entity).remove::<Uncover>();
+            continue;
+        }
+        // we animate then destroy the tile cover entity
+        let depth = depth.map_or(0, |d| d.0);
+        reveal_cover(&mut commands, entity, depth);
         let (coords, bomb, bomb_counter) = match parents.get(parent.0) {
             Ok(v) => v,
             Err(e) => {
@@ -37,25 +73,66 @@ pub fn uncover_tiles(
         };
 
         // We remove the entity from the board covered tile map
-        match board.try_uncover_tile(coords) {
-            None => log::debug!("Tried to uncover an already uncovered tile"),
-            Some(e) => log::debug!("Uncovered tile {} (entity: {:?})", coords, e),
-        }
-        if board.is_completed() {
-            log::info!("board completed");
-            board_completed_event_wr.send(BoardCompletedEvent);
-        }
+        let uncovered = match board.try_uncover_tile(coords, uncover.move_id) {
+            None => {
+                log::debug!("Tried to uncover an already uncovered tile");
+                false
+            }
+            Some(e) => {
+                log::debug!("Uncovered tile {} (entity: {:?})", coords, e);
+                board_change_ewr.send(BoardChangeEvent::TileUncovered {
+                    coordinates: *coords,
+                    move_id: uncover.move_id,
+                });
+                true
+            }
+        };
         if bomb.is_some() {
-            log::info!("bomb exploded");
-            bomb_explosion_event_wr.send(BombExplosionEvent);
+            if uncovered {
+                log::info!("bomb exploded");
+                if let Some(id) = uncover.move_id {
+                    board.history.record_explosion(id);
+                }
+                // The bomb rule decides if the explosion ends the game
+                if board.explode_bomb(*coords) {
+                    bomb_explosion_event_wr.send(BombExplosionEvent(*coords));
+                } else {
+                    let remaining = board.lives.unwrap_or_default();
+                    log::info!("life lost, {} remaining", remaining);
+                    spawn_exploded_flag(&mut commands, parent.0, &board_assets, board.tile_size);
+                    life_lost_event_wr.send(LifeLostEvent {
+                        coordinates: *coords,
+                        remaining,
+                    });
+                }
+            }
         }
         // If the tile is empty..
         else if bomb_counter.is_none() {
             // .. We propagate the uncovering by adding the `Uncover` component to adjacent tiles
             // which will then be removed next frame
             for entity in board.adjacent_covered_tiles(*coords) {
-                commands.entity(entity).insert(Uncover);
+                commands
+                    .entity(entity)
+                    .insert(*uncover)
+                    .insert(RevealDepth(depth + 1));
             }
         }
+        if uncovered && board.is_completed() {
+            log::info!("board completed");
+            board_completed_event_wr.send(BoardCompletedEvent);
+        }
     }
 }
+
+/// Spawns the flag of a bomb `tile` uncovered without ending the game
+pub(crate) fn spawn_exploded_flag(
+    commands: &mut Commands,
+    tile: Entity,
+    board_assets: &BoardAssets,
+    size: f32,
+) {
+    commands.entity(tile).with_children(|parent| {
+        board_assets.insert_exploded_flag(&mut parent.spawn(), Vec2::splat(size));
+    });
+}
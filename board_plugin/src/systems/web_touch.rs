@@ -0,0 +1,93 @@
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::log;
+use bevy::prelude::*;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{AddEventListenerOptions, HtmlCanvasElement, PointerEvent};
+
+/// Touches of the web canvas waiting to be sent as `TouchInput` events.
+///
+/// Winit turns the canvas pointer events into mouse events and never sends touches on the web,
+/// so the touch pointers are caught before winit sees them. Must be used as a resource
+#[derive(Debug, Default, Clone)]
+pub struct WebTouches(Arc<Mutex<Vec<TouchInput>>>);
+
+/// Listens to the touch pointers of the page canvases
+pub fn listen_web_touches(touches: Res<WebTouches>) {
+    let window = match web_sys::window() {
+        Some(w) => w,
+        None => {
+            log::error!("No web window to listen to touches on");
+            return;
+        }
+    };
+    // The browser would scroll or zoom the page instead of sending the touch moves
+    if let Some(Ok(canvases)) = window.document().map(|d| d.query_selector_all("canvas")) {
+        for canvas in (0..canvases.length()).filter_map(|i| canvases.get(i)) {
+            if let Ok(canvas) = canvas.dyn_into::<HtmlCanvasElement>() {
+                if let Err(e) = canvas.style().set_property("touch-action", "none") {
+                    log::error!("Failed to disable the canvas touch actions: {:?}", e);
+                }
+            }
+        }
+    }
+    for (name, phase) in [
+        ("pointerdown", TouchPhase::Started),
+        ("pointermove", TouchPhase::Moved),
+        ("pointerup", TouchPhase::Ended),
+        ("pointercancel", TouchPhase::Cancelled),
+    ] {
+        let queue = touches.0.clone();
+        let listener = Closure::wrap(Box::new(move |event: PointerEvent| {
+            let touch = match canvas_touch(&event, phase) {
+                Some(t) => t,
+                None => return,
+            };
+            // Winit would send the touch again as a mouse event
+            event.stop_propagation();
+            if let Ok(mut queue) = queue.lock() {
+                queue.push(touch);
+            }
+        }) as Box<dyn FnMut(PointerEvent)>);
+        // Window listeners capturing the event run before the canvas listeners of winit
+        let mut options = AddEventListenerOptions::new();
+        options.capture(true);
+        if let Err(e) = window.add_event_listener_with_callback_and_add_event_listener_options(
+            name,
+            listener.as_ref().unchecked_ref(),
+            &options,
+        ) {
+            log::error!("Failed to listen to {} events: {:?}", name, e);
+        }
+        // The listeners live as long as the page
+        listener.forget();
+    }
+}
+
+/// Touch of a canvas touch pointer `event`, in logical pixels from the bottom left of the canvas
+fn canvas_touch(event: &PointerEvent, phase: TouchPhase) -> Option<TouchInput> {
+    if event.pointer_type() != "touch" {
+        return None;
+    }
+    let canvas = event.target()?.dyn_into::<HtmlCanvasElement>().ok()?;
+    let rect = canvas.get_bounding_client_rect();
+    Some(TouchInput {
+        phase,
+        position: Vec2::new(
+            (event.client_x() as f64 - rect.left()) as f32,
+            (rect.bottom() - event.client_y() as f64) as f32,
+        ),
+        force: None,
+        id: event.pointer_id() as u64,
+    })
+}
+
+/// Sends the web canvas touches as `TouchInput` events
+pub fn send_web_touches(touches: Res<WebTouches>, mut touch_ewr: EventWriter<TouchInput>) {
+    if let Ok(mut queue) = touches.0.lock() {
+        for touch in queue.drain(..) {
+            touch_ewr.send(touch);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::saves;
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::events::BoardChangeEvent;
+use board_plugin::resources::{
+    Board, Difficulty, GameStatus, Playback, Replay, ReplayEvent, SavedGame,
+};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Snapshot of the running board when it was spawned, in the saves directory
+const SNAPSHOT_FILE: &str = "autosave.ron";
+/// Changes of the running board since its snapshot, one entry per line
+const JOURNAL_FILE: &str = "autosave.journal";
+
+/// Journaled change of the running game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum JournalChange {
+    Board(BoardChangeEvent),
+    /// Player action recorded in the replay
+    Replay(ReplayEvent),
+}
+
+/// Journaled change with the time spent playing when it was applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    elapsed: Duration,
+    change: JournalChange,
+}
+
+/// Autosave of the running game, restored after a crash.
+///
+/// A snapshot is written when a board is spawned, then every change is appended to a journal so
+/// huge boards are never rewritten. The autosave is removed when the board is left or the app quits
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        // Every board change of the frame is sent before the post update stage
+        app.add_system_to_stage(CoreStage::PostUpdate, autosave)
+            .add_system_set(
+                SystemSet::on_enter(AppState::RestoreAutosave).with_system(setup_restore_prompt),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::RestoreAutosave).with_system(restore_prompt_buttons),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::RestoreAutosave)
+                    .with_system(ui::despawn_screen::<RestorePromptScreen>),
+            );
+    }
+}
+
+/// Unfinished autosaved game offered on startup
+#[derive(Debug, Clone)]
+struct PendingRestore(SavedGame);
+
+/// Root node of the restore prompt
+#[derive(Debug, Copy, Clone, Component)]
+struct RestorePromptScreen;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum RestorePromptButton {
+    Restore,
+    Discard,
+}
+
+fn path(file: &str) -> Result<PathBuf, String> {
+    saves::saves_dir()
+        .map(|dir| dir.join(file))
+        .ok_or_else(|| "no data directory".to_string())
+}
+
+/// Replaces the autosave with the `saved_game` snapshot, returning the emptied journal
+fn start(saved_game: &SavedGame) -> Result<File, String> {
+    let snapshot = path(SNAPSHOT_FILE)?;
+    let journal = path(JOURNAL_FILE)?;
+    if let Some(dir) = snapshot.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // The old journal must never be applied to the new snapshot
+    let journal = File::create(journal).map_err(|e| e.to_string())?;
+    // The snapshot is replaced atomically by renaming a complete file over it, once it is on disk
+    let content = ron::to_string(saved_game).map_err(|e| e.to_string())?;
+    let temporary = snapshot.with_extension("ron.tmp");
+    let mut file = File::create(&temporary).map_err(|e| e.to_string())?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| e.to_string())?;
+    fs::rename(&temporary, &snapshot).map_err(|e| e.to_string())?;
+    log::debug!("Started autosave in {}", snapshot.display());
+    Ok(journal)
+}
+
+/// Reads the autosave snapshot and applies its journal
+fn load() -> Result<SavedGame, String> {
+    let content = fs::read_to_string(path(SNAPSHOT_FILE)?).map_err(|e| e.to_string())?;
+    let mut saved_game: SavedGame = ron::from_str(&content).map_err(|e| e.to_string())?;
+    let journal = fs::read_to_string(path(JOURNAL_FILE)?).unwrap_or_default();
+    for line in journal.lines() {
+        // Only the last line can be cut by a crash
+        let entry: JournalEntry = match ron::from_str(line) {
+            Ok(e) => e,
+            Err(e) => {
+                log::warn!("Ignoring the end of the autosave journal: {}", e);
+                break;
+            }
+        };
+        match entry.change {
+            JournalChange::Board(change) => saved_game.apply(change),
+            JournalChange::Replay(event) => {
+                if let Some(replay) = saved_game.replay.as_mut() {
+                    // The recording resumes from its last action
+                    replay.duration = event.time;
+                    replay.events.push(event);
+                }
+            }
+        }
+        saved_game.elapsed = entry.elapsed;
+    }
+    saved_game.validate()?;
+    Ok(saved_game)
+}
+
+/// Removes the autosave files
+pub fn delete() {
+    for file in [SNAPSHOT_FILE, JOURNAL_FILE] {
+        if let Ok(path) = path(file) {
+            // A missing file is already deleted
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Writes the running board snapshot when it is spawned, then appends its changes and its
+/// recorded replay actions to the journal
+fn autosave(
+    board: Option<Res<Board>>,
+    status: Option<Res<GameStatus>>,
+    replay: Option<Res<Replay>>,
+    playback: Option<Res<Playback>>,
+    mut board_change_evr: EventReader<BoardChangeEvent>,
+    mut board_entity: Local<Option<Entity>>,
+    mut journal: Local<Option<File>>,
+    // Replay actions in the snapshot or the journal
+    mut journaled_replay: Local<usize>,
+) {
+    // A played back replay is not a game to restore
+    let (board, status) = match (board, status, playback) {
+        (Some(b), Some(s), None) => (b, s),
+        _ => {
+            board_change_evr.iter().for_each(drop);
+            if board_entity.take().is_some() {
+                *journal = None;
+                delete();
+            }
+            return;
+        }
+    };
+    if *board_entity != Some(board.entity) {
+        *board_entity = Some(board.entity);
+        // The changes of this frame are already in the snapshot
+        board_change_evr.iter().for_each(drop);
+        *journaled_replay = replay.as_ref().map_or(0, |r| r.events.len());
+        *journal = match start(&SavedGame::new(&board, &status, replay.as_deref())) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::error!("Failed to start the autosave: {}", e);
+                None
+            }
+        };
+        return;
+    }
+    let replay_events = replay
+        .as_ref()
+        .and_then(|r| r.events.get(*journaled_replay..))
+        .unwrap_or_default();
+    *journaled_replay += replay_events.len();
+    let changes = board_change_evr
+        .iter()
+        .map(|c| JournalChange::Board(*c))
+        .chain(replay_events.iter().map(|e| JournalChange::Replay(*e)));
+    let mut lines = String::new();
+    for change in changes {
+        let entry = JournalEntry {
+            elapsed: status.elapsed,
+            change,
+        };
+        match ron::to_string(&entry) {
+            Ok(line) => {
+                lines.push_str(&line);
+                lines.push('\n');
+            }
+            Err(e) => log::error!("Failed to serialize {:?}: {}", entry, e),
+        }
+    }
+    if lines.is_empty() {
+        return;
+    }
+    if let Some(file) = journal.as_mut() {
+        // A single write per frame, a crash can only cut its end
+        if let Err(e) = file
+            .write_all(lines.as_bytes())
+            .and_then(|_| file.sync_data())
+        {
+            log::error!("Failed to write the autosave journal: {}", e);
+            *journal = None;
+        }
+    }
+}
+
+/// Offers to restore the autosave left by a crash, unless its game was over or not started
+pub fn offer_restore(commands: &mut Commands, state: &mut State<AppState>) {
+    let saved_game = match load() {
+        Ok(s) => s,
+        Err(e) => {
+            log::debug!("No autosave to restore: {}", e);
+            return;
+        }
+    };
+    if saved_game.is_finished() || saved_game.history.moves().is_empty() {
+        delete();
+        return;
+    }
+    log::info!("found an unfinished autosave");
+    commands.insert_resource(PendingRestore(saved_game));
+    state.set(AppState::RestoreAutosave).unwrap();
+}
+
+fn setup_restore_prompt(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    pending: Res<PendingRestore>,
+) {
+    let font = ui::font(&asset_server);
+    let description = format!(
+        "{} game, {:.0}s played",
+        Difficulty::from(&pending.0.options),
+        pending.0.elapsed.as_secs_f32()
+    );
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Restore Prompt"))
+        .insert(RestorePromptScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Restore game?", font.clone(), 40.));
+            parent.spawn_bundle(ui::text_bundle(description, font.clone(), 20.));
+            ui::spawn_button(
+                parent,
+                font.clone(),
+                "Restore",
+                300.,
+                RestorePromptButton::Restore,
+            );
+            ui::spawn_button(
+                parent,
+                font.clone(),
+                "Discard",
+                300.,
+                RestorePromptButton::Discard,
+            );
+        });
+}
+
+fn restore_prompt_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    pending: Res<PendingRestore>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&RestorePromptButton>,
+) {
+    for ButtonActivated(entity) in activated_evr.iter() {
+        match buttons.get(*entity) {
+            Ok(RestorePromptButton::Restore) => {
+                log::info!("restoring the autosave");
+                saves::start_saved_game(&mut commands, &mut state, pending.0.clone());
+            }
+            Ok(RestorePromptButton::Discard) => {
+                log::info!("discarding the autosave");
+                delete();
+                state.set(AppState::Out).unwrap();
+            }
+            Err(_) => continue,
+        }
+        commands.remove_resource::<PendingRestore>();
+        return;
+    }
+}
@@ -0,0 +1,255 @@
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{InputAction, InputBinding, InputBindings, InputDevice};
+
+/// Input bindings screen, shown in `AppState::Controls`.
+///
+/// Activating an action waits for the next key, mouse or gamepad button to bind it, Escape cancels.
+/// An input bound to another action is moved, which is shown below the actions
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingRebind>()
+            .init_resource::<RebindConflict>()
+            .add_system_set(SystemSet::on_enter(AppState::Controls).with_system(setup_controls))
+            .add_system_set(
+                SystemSet::on_update(AppState::Controls)
+                    .with_system(controls_buttons)
+                    .with_system(update_controls_labels),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Controls)
+                    .with_system(ui::despawn_screen::<ControlsScreen>),
+            );
+    }
+}
+
+/// Action waiting for its new input
+#[derive(Debug, Default)]
+struct PendingRebind(Option<InputAction>);
+
+/// Actions the last bound input was taken from, as displayed
+#[derive(Debug, Default)]
+struct RebindConflict(Option<String>);
+
+/// Text displaying the `RebindConflict`
+#[derive(Debug, Copy, Clone, Component)]
+struct ConflictText;
+
+/// Root node of the controls screen
+#[derive(Debug, Copy, Clone, Component)]
+struct ControlsScreen;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum ControlsButton {
+    Action(InputAction),
+    Preset,
+    Back,
+}
+
+/// Actions listed in each column of the screen
+const ACTIONS_PER_COLUMN: usize = 12;
+
+fn setup_controls(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pending: ResMut<PendingRebind>,
+    mut conflict: ResMut<RebindConflict>,
+) {
+    pending.0 = None;
+    conflict.0 = None;
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Controls Screen"))
+        .insert(ControlsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Controls", font.clone(), 40.));
+            // Labels are filled by `update_controls_labels`
+            parent
+                .spawn_bundle(ui::row_bundle())
+                .with_children(|parent| {
+                    for actions in InputAction::ALL.chunks(ACTIONS_PER_COLUMN) {
+                        parent
+                            .spawn_bundle(ui::column_bundle())
+                            .with_children(|parent| {
+                                for action in actions {
+                                    ui::spawn_sized_button(
+                                        parent,
+                                        font.clone(),
+                                        "",
+                                        Vec2::new(330., 30.),
+                                        10.,
+                                        ControlsButton::Action(*action),
+                                    );
+                                }
+                            });
+                    }
+                });
+            parent
+                .spawn_bundle(ui::text_bundle("", font.clone(), 20.))
+                .insert(ConflictText);
+            parent
+                .spawn_bundle(ui::row_bundle())
+                .with_children(|parent| {
+                    ui::spawn_button(parent, font.clone(), "", 330., ControlsButton::Preset);
+                    ui::spawn_button(parent, font.clone(), "Back", 330., ControlsButton::Back);
+                });
+        });
+}
+
+fn controls_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut bindings: ResMut<InputBindings>,
+    mut pending: ResMut<PendingRebind>,
+    mut conflict: ResMut<RebindConflict>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&ControlsButton>,
+) {
+    if let Some(action) = pending.0 {
+        // The input pressed to bind may also activate a button
+        activated_evr.iter().for_each(drop);
+        if keys.just_pressed(KeyCode::Escape) {
+            pending.0 = None;
+        } else if let Some(binding) = pressed_binding(&keys, &mouse_buttons, &gamepad_buttons) {
+            log::info!("binding {} to {}", binding, action);
+            let taken_from: Vec<String> = bindings
+                .rebind(action, binding)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            conflict.0 = match taken_from.is_empty() {
+                true => None,
+                false => Some(format!(
+                    "{} was unbound from {}",
+                    binding,
+                    taken_from.join(", ")
+                )),
+            };
+            pending.0 = None;
+        }
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Settings).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        match buttons.get(*entity) {
+            Ok(ControlsButton::Action(action)) => {
+                pending.0 = Some(*action);
+                conflict.0 = None;
+            }
+            Ok(ControlsButton::Preset) => {
+                conflict.0 = None;
+                *bindings = match *bindings == InputBindings::right_handed() {
+                    true => InputBindings::left_handed(),
+                    false => InputBindings::right_handed(),
+                };
+            }
+            Ok(ControlsButton::Back) => {
+                state.set(AppState::Settings).unwrap();
+                return;
+            }
+            Err(_) => continue,
+        }
+    }
+}
+
+/// Binding of the input pressed this frame, a control key only modifies the other keys
+fn pressed_binding(
+    keys: &Input<KeyCode>,
+    mouse_buttons: &Input<MouseButton>,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> Option<InputBinding> {
+    let ctrl_keys = [KeyCode::LControl, KeyCode::RControl];
+    let key = keys
+        .get_just_pressed()
+        .find(|k| !ctrl_keys.contains(k))
+        .map(|k| match keys.any_pressed(ctrl_keys) {
+            true => InputBinding::CtrlKey(*k),
+            false => InputBinding::Key(*k),
+        });
+    key.or_else(|| {
+        mouse_buttons
+            .get_just_pressed()
+            .next()
+            .map(|b| InputBinding::Mouse(*b))
+    })
+    .or_else(|| {
+        gamepad_buttons
+            .get_just_pressed()
+            .next()
+            .map(|b| InputBinding::Gamepad(b.1))
+    })
+}
+
+fn update_controls_labels(
+    bindings: Res<InputBindings>,
+    pending: Res<PendingRebind>,
+    conflict: Res<RebindConflict>,
+    buttons: Query<(&ControlsButton, &Children)>,
+    added_buttons: Query<(), Added<ControlsButton>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, Without<ConflictText>>,
+        QueryState<&mut Text, With<ConflictText>>,
+    )>,
+) {
+    if !bindings.is_changed()
+        && !pending.is_changed()
+        && !conflict.is_changed()
+        && added_buttons.iter().next().is_none()
+    {
+        return;
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = conflict.0.clone().unwrap_or_default();
+    }
+    for (button, children) in buttons.iter() {
+        let label = match button {
+            ControlsButton::Action(action) if pending.0 == Some(*action) => {
+                format!("{}: press an input", action)
+            }
+            ControlsButton::Action(action) => {
+                let mut inputs: Vec<String> = bindings
+                    .get(*action)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                // Holding the reveal and flag mouse buttons together also chords
+                let mouse = |action| {
+                    bindings
+                        .get(action)
+                        .iter()
+                        .find(|b| b.device() == InputDevice::Mouse)
+                        .copied()
+                };
+                if let (InputAction::Chord, Some(reveal), Some(flag)) =
+                    (action, mouse(InputAction::Reveal), mouse(InputAction::Flag))
+                {
+                    inputs.push(format!("{}+{}", reveal, flag));
+                }
+                format!("{}: {}", action, inputs.join(" "))
+            }
+            ControlsButton::Preset if *bindings == InputBindings::right_handed() => {
+                "Preset: Right-handed".to_string()
+            }
+            ControlsButton::Preset if *bindings == InputBindings::left_handed() => {
+                "Preset: Left-handed".to_string()
+            }
+            ControlsButton::Preset => "Preset: Custom".to_string(),
+            ControlsButton::Back => continue,
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = texts.q0().get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::ui::{self, ButtonActivated, Disabled};
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{BoardOptions, Difficulty};
+
+/// Custom game dialog, shown in `AppState::CustomGame`
+pub struct CustomGamePlugin;
+
+impl Plugin for CustomGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::CustomGame).with_system(setup_dialog),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::CustomGame)
+                .with_system(dialog_buttons)
+                .with_system(update_dialog),
+        )
+        .add_system_set(
+            SystemSet::on_exit(AppState::CustomGame)
+                .with_system(ui::despawn_screen::<CustomGameDialog>),
+        );
+    }
+}
+
+/// Edited custom board values
+#[derive(Debug, Copy, Clone)]
+struct CustomGame {
+    width: u16,
+    height: u16,
+    bomb_count: u16,
+}
+
+impl CustomGame {
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Custom {
+            width: self.width,
+            height: self.height,
+            bomb_count: self.bomb_count,
+        }
+    }
+
+    fn field_mut(&mut self, field: Field) -> &mut u16 {
+        match field {
+            Field::Width => &mut self.width,
+            Field::Height => &mut self.height,
+            Field::BombCount => &mut self.bomb_count,
+        }
+    }
+}
+
+/// Root node of the dialog
+#[derive(Debug, Copy, Clone, Component)]
+struct CustomGameDialog;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Field {
+    Width,
+    Height,
+    BombCount,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum DialogButton {
+    Decrease(Field),
+    Increase(Field),
+    Start,
+    Cancel,
+}
+
+/// Text displaying a field value
+#[derive(Debug, Copy, Clone, Component)]
+struct FieldText(Field);
+
+/// Text displaying the validation error
+#[derive(Debug, Copy, Clone, Component)]
+struct ErrorText;
+
+fn setup_dialog(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    options: Option<Res<BoardOptions>>,
+) {
+    let custom = match options {
+        None => CustomGame {
+            width: 9,
+            height: 9,
+            bomb_count: 10,
+        },
+        Some(o) => CustomGame {
+            width: o.map_size.0,
+            height: o.map_size.1,
+            bomb_count: o.bomb_count,
+        },
+    };
+    commands.insert_resource(custom);
+
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgba(0., 0., 0., 0.85)))
+        .insert(Name::new("Custom Game Dialog"))
+        .insert(CustomGameDialog)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Custom game", font.clone(), 40.));
+            for (label, field) in [
+                ("Width", Field::Width),
+                ("Height", Field::Height),
+                ("Mines", Field::BombCount),
+            ] {
+                parent.spawn_bundle(ui::row_bundle()).with_children(|parent| {
+                    parent.spawn_bundle(ui::text_bundle(label, font.clone(), 20.));
+                    ui::spawn_button(
+                        parent,
+                        font.clone(),
+                        "-",
+                        40.,
+                        DialogButton::Decrease(field),
+                    );
+                    parent
+                        .spawn_bundle(ui::text_bundle("", font.clone(), 20.))
+                        .insert(FieldText(field));
+                    ui::spawn_button(
+                        parent,
+                        font.clone(),
+                        "+",
+                        40.,
+                        DialogButton::Increase(field),
+                    );
+                });
+            }
+            parent
+                .spawn_bundle(ui::text_bundle("", font.clone(), 14.))
+                .insert(ErrorText);
+            parent.spawn_bundle(ui::row_bundle()).with_children(|parent| {
+                ui::spawn_button(parent, font.clone(), "Start", 150., DialogButton::Start);
+                ui::spawn_button(parent, font.clone(), "Cancel", 150., DialogButton::Cancel);
+            });
+        });
+}
+
+fn dialog_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    mut custom: ResMut<CustomGame>,
+    options: Option<Res<BoardOptions>>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&DialogButton>,
+) {
+    // Shift changes the values by steps of ten
+    let step = if keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+        10
+    } else {
+        1
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Out).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        let button = match buttons.get(*entity) {
+            Ok(b) => b,
+            Err(_) => continue,
+        };
+        match button {
+            DialogButton::Decrease(field) => {
+                let value = custom.field_mut(*field);
+                *value = value.saturating_sub(step);
+            }
+            DialogButton::Increase(field) => {
+                let value = custom.field_mut(*field);
+                *value = value.saturating_add(step);
+            }
+            DialogButton::Start => {
+                let difficulty = custom.difficulty();
+                if let Err(e) = difficulty.validate() {
+                    log::warn!("Invalid custom game: {}", e);
+                    continue;
+                }
+                log::info!("starting {} game", difficulty);
+                commands.insert_resource(difficulty);
+                commands.insert_resource(crate::board_options(options.as_deref(), difficulty));
+                state.set(AppState::InGame).unwrap();
+            }
+            DialogButton::Cancel => {
+                state.set(AppState::Out).unwrap();
+            }
+        }
+    }
+}
+
+fn update_dialog(
+    mut commands: Commands,
+    custom: Res<CustomGame>,
+    mut field_texts: Query<(&mut Text, &FieldText), Without<ErrorText>>,
+    mut error_texts: Query<&mut Text, With<ErrorText>>,
+    mut buttons: Query<(Entity, &DialogButton, &mut UiColor)>,
+) {
+    if !custom.is_changed() {
+        return;
+    }
+    for (mut text, FieldText(field)) in field_texts.iter_mut() {
+        let value = match field {
+            Field::Width => custom.width,
+            Field::Height => custom.height,
+            Field::BombCount => custom.bomb_count,
+        };
+        text.sections[0].value = format!("{:>3}", value);
+    }
+    let validation = custom.difficulty().validate();
+    for mut text in error_texts.iter_mut() {
+        text.sections[0].value = match validation {
+            Ok(()) => String::new(),
+            Err(e) => e.to_string(),
+        };
+    }
+    // The start button is disabled while the values are invalid
+    for (entity, button, mut color) in buttons.iter_mut() {
+        if *button != DialogButton::Start {
+            continue;
+        }
+        if validation.is_ok() {
+            commands.entity(entity).remove::<Disabled>();
+            *color = ui::NORMAL_BUTTON.into();
+        } else {
+            commands.entity(entity).insert(Disabled);
+            *color = ui::DISABLED_BUTTON.into();
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::layouts;
+use crate::statistics::Statistics;
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::events::{BoardCompletedEvent, BombExplosionEvent, ResetBoard, UndoEvent};
+use board_plugin::resources::{Board, Difficulty, GameStatus};
+
+/// Game over and victory screens, shown over the board
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Statistics::load())
+            .add_system_set(SystemSet::on_update(AppState::InGame).with_system(game_end_handler))
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(setup_game_over))
+            .add_system_set(SystemSet::on_update(AppState::GameOver).with_system(end_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver).with_system(ui::despawn_screen::<EndScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Victory).with_system(setup_victory))
+            .add_system_set(SystemSet::on_update(AppState::Victory).with_system(end_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Victory).with_system(ui::despawn_screen::<EndScreen>),
+            );
+    }
+}
+
+/// Result of the last finished game
+#[derive(Debug, Clone)]
+struct GameResult {
+    difficulty: Difficulty,
+    status: GameStatus,
+    new_record: bool,
+}
+
+/// Root node of the end screens
+#[derive(Debug, Copy, Clone, Component)]
+struct EndScreen;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum EndButton {
+    Retry,
+    Undo,
+    NewGame,
+    ExportLayout,
+    MainMenu,
+}
+
+/// Records the finished games and shows the matching end screen
+fn game_end_handler(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    mut statistics: ResMut<Statistics>,
+    // The last recorded board, a board finishing again after an undo is not recorded twice
+    mut recorded: Local<Option<Entity>>,
+    board: Res<Board>,
+    status: Res<GameStatus>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+) {
+    let won = board_completed_evr.iter().count() > 0;
+    let lost = bomb_explosion_evr.iter().count() > 0;
+    if !won && !lost {
+        return;
+    }
+    // A resumed game is recorded with its own difficulty
+    let difficulty = Difficulty::from(&board.options);
+    let mut new_record = false;
+    if *recorded != Some(board.entity) {
+        *recorded = Some(board.entity);
+        let eligible = !board.options.practice_mode && board.history.undo_count() == 0;
+        new_record = statistics.record(&difficulty, won, status.elapsed, eligible);
+        if let Err(e) = statistics.save() {
+            log::error!("Failed to save statistics: {}", e);
+        }
+    }
+    commands.insert_resource(GameResult {
+        difficulty,
+        status: status.clone(),
+        new_record,
+    });
+    if won {
+        log::info!("victory");
+        state.push(AppState::Victory).unwrap();
+    } else {
+        log::info!("game over");
+        state.push(AppState::GameOver).unwrap();
+    }
+}
+
+fn setup_game_over(mut commands: Commands, asset_server: Res<AssetServer>, board: Res<Board>) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgba(0.3, 0., 0., 0.6)))
+        .insert(Name::new("Game Over Screen"))
+        .insert(EndScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Game over", font.clone(), 50.));
+            ui::spawn_button(parent, font.clone(), "Retry", 300., EndButton::Retry);
+            // Only practice games can take a fatal move back
+            if board.options.practice_mode {
+                ui::spawn_button(parent, font.clone(), "Undo", 300., EndButton::Undo);
+            }
+            ui::spawn_button(parent, font.clone(), "New game", 300., EndButton::NewGame);
+            ui::spawn_button(
+                parent,
+                font.clone(),
+                "Export layout",
+                300.,
+                EndButton::ExportLayout,
+            );
+            ui::spawn_button(parent, font.clone(), "Main menu", 300., EndButton::MainMenu);
+        });
+}
+
+fn setup_victory(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    result: Res<GameResult>,
+    statistics: Res<Statistics>,
+) {
+    let font = ui::font(&asset_server);
+    let stats = statistics.get(&result.difficulty);
+    let mut lines = vec![
+        format!("{}", result.difficulty),
+        format!("Time: {:.1}s", result.status.elapsed.as_secs_f32()),
+    ];
+    if let Some(best) = stats.best_time {
+        lines.push(format!("Best: {:.1}s", best.as_secs_f32()));
+    }
+    lines.push(format!("Won {} of {} games", stats.won, stats.played));
+    if result.new_record {
+        lines.push("New record!".to_string());
+    }
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgba(0., 0.2, 0., 0.6)))
+        .insert(Name::new("Victory Screen"))
+        .insert(EndScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Victory!", font.clone(), 50.));
+            for line in lines {
+                parent.spawn_bundle(ui::text_bundle(line, font.clone(), 20.));
+            }
+            ui::spawn_button(parent, font.clone(), "New game", 300., EndButton::NewGame);
+            ui::spawn_button(
+                parent,
+                font.clone(),
+                "Export layout",
+                300.,
+                EndButton::ExportLayout,
+            );
+            ui::spawn_button(parent, font.clone(), "Main menu", 300., EndButton::MainMenu);
+        });
+}
+
+fn end_buttons(
+    mut state: ResMut<State<AppState>>,
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<(&EndButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    mut reset_board_ewr: EventWriter<ResetBoard>,
+    mut undo_ewr: EventWriter<UndoEvent>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.replace(AppState::Out).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        let (button, children) = match buttons.get(*entity) {
+            Ok(b) => b,
+            Err(_) => continue,
+        };
+        match button {
+            EndButton::Retry => {
+                state.pop().unwrap();
+                reset_board_ewr.send(ResetBoard { same_layout: true });
+            }
+            EndButton::Undo => {
+                state.pop().unwrap();
+                undo_ewr.send(UndoEvent);
+            }
+            EndButton::NewGame => {
+                state.pop().unwrap();
+                reset_board_ewr.send(ResetBoard { same_layout: false });
+            }
+            // Exporting keeps the end screen
+            EndButton::ExportLayout => {
+                if let Err(e) = layouts::export(&board.tile_map) {
+                    log::error!("Failed to export the layout: {}", e);
+                    continue;
+                }
+                for child in children.iter() {
+                    if let Ok(mut text) = texts.get_mut(*child) {
+                        text.sections[0].value = "Layout exported".to_string();
+                    }
+                }
+                continue;
+            }
+            EndButton::MainMenu => state.replace(AppState::Out).unwrap(),
+        }
+        return;
+    }
+}
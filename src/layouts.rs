@@ -0,0 +1,93 @@
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{BoardLayout, Difficulty, LayoutFormat, TileMap};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of recent layouts listed in the load game screen
+pub const LISTED_LAYOUTS: usize = 5;
+
+/// Directory of the layout files, `None` if the platform has no data directory
+pub fn layouts_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("bevy_mines").join("layouts"))
+}
+
+/// Reads the layout file at `path`, in the format given by its extension
+pub fn load(path: &Path) -> Result<TileMap, String> {
+    let format = LayoutFormat::from_path(path)
+        .ok_or_else(|| format!("unknown layout format of {}", path.display()))?;
+    let content = fs::read(path).map_err(|e| e.to_string())?;
+    let tile_map = format.read(&content)?;
+    log::info!("Loaded layout from {}", path.display());
+    Ok(tile_map)
+}
+
+/// Writes `tile_map` in every layout format, returning the path of the files without extension
+pub fn export(tile_map: &TileMap) -> Result<PathBuf, String> {
+    let dir = layouts_dir().ok_or_else(|| "no data directory".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?;
+    let path = dir.join(timestamp.as_secs().to_string());
+    for format in LayoutFormat::ALL {
+        // Large maps have no MBF layout
+        let content = match format.write(tile_map) {
+            Ok(c) => c,
+            Err(e) => {
+                log::warn!("No {:?} layout: {}", format, e);
+                continue;
+            }
+        };
+        let file = path.with_extension(format.extension());
+        fs::write(&file, content).map_err(|e| e.to_string())?;
+        log::info!("Exported layout to {}", file.display());
+    }
+    Ok(path)
+}
+
+/// Paths of the most recent layouts, newest first. A layout exported in several formats is listed
+/// once
+pub fn recent_layouts() -> Vec<PathBuf> {
+    let entries = match layouts_dir().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return Vec::new(),
+    };
+    let mut paths: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| LayoutFormat::from_path(&e.path()).is_some())
+        // Layouts can be copied in the directory, so they are sorted by modification time
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    paths.sort();
+    let mut stems = HashSet::new();
+    paths
+        .into_iter()
+        .rev()
+        .filter(|(_, path)| stems.insert(path.file_stem().map(ToOwned::to_owned)))
+        .take(LISTED_LAYOUTS)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Starts a game with the bombs of `tile_map`, which must fit a custom game. The board keeps the
+/// player's other options without changing them
+pub fn start_layout_game(
+    commands: &mut Commands,
+    state: &mut State<AppState>,
+    tile_map: TileMap,
+) -> Result<(), String> {
+    Difficulty::Custom {
+        width: tile_map.width(),
+        height: tile_map.height(),
+        bomb_count: tile_map.bomb_count(),
+    }
+    .validate()
+    .map_err(|e| e.to_string())?;
+    commands.insert_resource(BoardLayout(tile_map));
+    state.set(AppState::InGame).unwrap();
+    Ok(())
+}
@@ -1,19 +1,59 @@
+// Bevy systems take their resources and queries as arguments
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod autosave;
+mod controls;
+mod custom_game;
+mod game_over;
+mod layouts;
+mod menu;
+mod pause;
+mod replays;
+mod saves;
+mod settings;
+mod statistics;
+mod ui;
+
 use bevy::log;
 use bevy::prelude::*;
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::WorldInspectorPlugin;
 
-use board_plugin::resources::BoardOptions;
-use board_plugin::resources::{BoardAssets, SpriteMaterial};
+use autosave::AutosavePlugin;
+use board_plugin::components::BoardCamera;
+use board_plugin::events::{NewGame, RedoEvent, ResetBoard, UndoEvent};
+use board_plugin::resources::ActiveTheme;
+use board_plugin::resources::{ActionInput, InputAction};
+use board_plugin::resources::{BoardOptions, Difficulty};
 use board_plugin::BoardPlugin;
+use controls::ControlsPlugin;
+use custom_game::CustomGamePlugin;
+use game_over::GameOverPlugin;
+use menu::MenuPlugin;
+use pause::PausePlugin;
+use replays::ReplaysPlugin;
+use saves::SavesPlugin;
+use settings::Settings;
+use std::path::Path;
+use ui::UiPlugin;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum AppState {
     InGame,
-    Reseting,
     Pause,
     Out,
+    DifficultyMenu,
+    CustomGame,
+    Statistics,
+    Settings,
+    Controls,
+    LoadGame,
+    RestoreAutosave,
+    Replays,
+    Playback,
+    GameOver,
+    Victory,
 }
 
 fn main() {
@@ -31,8 +71,21 @@ fn main() {
         // mine board plugin
         .add_plugin(BoardPlugin {
             running_state: AppState::InGame,
+            playback_state: Some(AppState::Playback),
         })
+        .add_plugin(UiPlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(CustomGamePlugin)
+        .add_plugin(GameOverPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(ControlsPlugin)
+        .add_plugin(SavesPlugin)
+        .add_plugin(AutosavePlugin)
+        .add_plugin(ReplaysPlugin)
         .add_system(state_handler)
+        // Replays send their own undo and redo events
+        .add_system_set(SystemSet::on_update(AppState::InGame).with_system(history_handler))
+        .add_system(settings::save_settings)
         .add_startup_system(setup_board)
         .add_startup_system(camera_setup);
 
@@ -45,80 +98,132 @@ fn main() {
 
 fn camera_setup(mut commands: Commands) {
     // 2D orthographic camera
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(BoardCamera);
+    // UI camera for the HUD
+    commands.spawn_bundle(UiCameraBundle::default());
 }
 
 fn setup_board(
     mut commands: Commands,
-    mut state: ResMut<State<AppState>>,
     asset_server: Res<AssetServer>,
+    mut state: ResMut<State<AppState>>,
 ) {
-    // Board plugin options
-    commands.insert_resource(BoardOptions {
-        map_size: (20, 20),
-        bomb_count: 60,
-        tile_padding: 1.,
-        safe_start: true,
-        ..Default::default()
-    });
-    // Board assets
-    commands.insert_resource(BoardAssets {
-        label: "Default".to_string(),
-        board_material: SpriteMaterial {
-            color: Color::WHITE,
-            ..Default::default()
-        },
-        tile_material: SpriteMaterial {
-            color: Color::DARK_GRAY,
-            ..Default::default()
-        },
-        covered_tile_material: SpriteMaterial {
-            color: Color::GRAY,
-            ..Default::default()
-        },
-        bomb_counter_font: asset_server.load("fonts/pixeled.ttf"),
-        bomb_counter_colors: BoardAssets::default_colors(),
-        flag_material: SpriteMaterial {
-            texture: asset_server.load("sprites/flag.png"),
-            color: Color::WHITE,
+    // Board plugin options, from the settings file
+    let settings = Settings::load();
+    commands.insert_resource(settings.difficulty);
+    commands.insert_resource(settings.board_options.clone());
+    commands.insert_resource(settings.input_bindings);
+    // Board theme, theme files are hot reloaded
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = asset_server.watch_for_changes() {
+        log::warn!("Theme hot reloading is disabled: {:?}", e);
+    }
+    commands.insert_resource(ActiveTheme(asset_server.load(settings.theme.as_str())));
+    // A layout file given as argument is played, else the game left on quit is resumed instead
+    // of showing the menu, then the game left by a crash is offered
+    if let Some(path) = std::env::args().nth(1) {
+        let started = layouts::load(Path::new(&path))
+            .and_then(|tile_map| layouts::start_layout_game(&mut commands, &mut state, tile_map));
+        match started {
+            Ok(()) => return,
+            Err(e) => log::error!("Failed to load layout {}: {}", path, e),
+        }
+    }
+    if !saves::resume_on_launch(&mut commands, &mut state) {
+        autosave::offer_restore(&mut commands, &mut state);
+    }
+}
+
+/// Board options of the game for a given difficulty, keeping the `current` other options
+fn board_options(current: Option<&BoardOptions>, difficulty: Difficulty) -> BoardOptions {
+    match current {
+        None => BoardOptions {
+            tile_padding: 1.,
+            safe_start: true,
+            ..difficulty.into()
         },
-        bomb_material: SpriteMaterial {
-            texture: asset_server.load("sprites/bomb.png"),
-            color: Color::WHITE,
+        Some(options) => BoardOptions {
+            map_size: difficulty.map_size(),
+            bomb_count: difficulty.bomb_count(),
+            ..options.clone()
         },
-    });
-    // Plugin activation
-    state.set(AppState::InGame).unwrap();
+    }
 }
 
-fn state_handler(mut state: ResMut<State<AppState>>, keys: Res<Input<KeyCode>>) {
-    if state.current() == &AppState::Reseting {
-        state.set(AppState::InGame).unwrap();
-        return;
-    }
-    if keys.just_pressed(KeyCode::Escape) {
-        log::debug!("pause detected");
+fn state_handler(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    options: Option<Res<BoardOptions>>,
+    input: ActionInput,
+    mut reset_board_ewr: EventWriter<ResetBoard>,
+    mut new_game_ewr: EventWriter<NewGame>,
+) {
+    if input.just_pressed(InputAction::ClearGame) {
+        log::debug!("clear game detected");
         if state.current() == &AppState::InGame {
-            log::info!("entering pause");
-            state.push(AppState::Pause).unwrap();
+            log::info!("clearing game");
+            state.set(AppState::Out).unwrap();
         }
-        if state.current() == &AppState::Pause {
-            log::info!("leaving pause");
-            state.pop().unwrap();
+    }
+    if input.just_pressed(InputAction::NewGame) {
+        log::debug!("new game detected");
+        if state.current() == &AppState::Out {
+            log::info!("starting game");
+            state.set(AppState::InGame).unwrap();
+        } else if state.current() == &AppState::InGame {
+            log::info!("reseting game");
+            reset_board_ewr.send(ResetBoard { same_layout: false });
         }
     }
-    if keys.just_pressed(KeyCode::C) {
-        log::debug!("C detected");
+    for (action, difficulty) in [
+        (InputAction::BeginnerGame, Difficulty::Beginner),
+        (InputAction::IntermediateGame, Difficulty::Intermediate),
+        (InputAction::ExpertGame, Difficulty::Expert),
+    ] {
+        if !input.just_pressed(action) {
+            continue;
+        }
+        let options = board_options(options.as_deref(), difficulty);
         if state.current() == &AppState::InGame {
-            log::info!("clearing game");
-            state.set(AppState::Out).unwrap();
+            log::info!("starting {} game", difficulty);
+            commands.insert_resource(difficulty);
+            new_game_ewr.send(NewGame(options));
+        } else if state.current() == &AppState::Out {
+            log::info!("starting {} game", difficulty);
+            commands.insert_resource(difficulty);
+            commands.insert_resource(options);
+            state.set(AppState::InGame).unwrap();
         }
     }
-    if keys.just_pressed(KeyCode::G) {
-        log::debug!("G detected");
+    if input.just_pressed(InputAction::CustomGame) {
+        log::debug!("custom game detected");
         if state.current() == &AppState::Out || state.current() == &AppState::InGame {
-            log::info!("reseting game");
-            state.set(AppState::Reseting).unwrap();
+            log::info!("opening custom game dialog");
+            state.set(AppState::CustomGame).unwrap();
         }
     }
+    if input.just_pressed(InputAction::RestartBoard) {
+        log::debug!("restart detected");
+        if state.current() == &AppState::InGame {
+            log::info!("restarting board");
+            reset_board_ewr.send(ResetBoard { same_layout: true });
+        }
+    }
+}
+
+fn history_handler(
+    input: ActionInput,
+    mut undo_ewr: EventWriter<UndoEvent>,
+    mut redo_ewr: EventWriter<RedoEvent>,
+) {
+    if input.just_pressed(InputAction::Undo) {
+        log::debug!("undo detected");
+        undo_ewr.send(UndoEvent);
+    }
+    if input.just_pressed(InputAction::Redo) {
+        log::debug!("redo detected");
+        redo_ewr.send(RedoEvent);
+    }
 }
@@ -0,0 +1,392 @@
+use crate::settings::THEMES;
+use crate::statistics::Statistics;
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::app::AppExit;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{ActiveTheme, BoardAssets, BoardOptions, BombRule, Difficulty};
+
+/// Main menu and its sub screens: difficulty, statistics and settings
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Out).with_system(setup_main_menu))
+            .add_system_set(SystemSet::on_update(AppState::Out).with_system(main_menu_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Out).with_system(ui::despawn_screen::<MenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::DifficultyMenu).with_system(setup_difficulty_menu),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::DifficultyMenu)
+                    .with_system(difficulty_menu_buttons),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::DifficultyMenu)
+                    .with_system(ui::despawn_screen::<MenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Statistics).with_system(setup_statistics_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Statistics).with_system(back_button::<BackButton>),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Statistics)
+                    .with_system(ui::despawn_screen::<MenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Settings).with_system(setup_settings_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Settings)
+                    .with_system(settings_buttons)
+                    .with_system(update_settings_labels),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Settings)
+                    .with_system(ui::despawn_screen::<MenuScreen>),
+            );
+    }
+}
+
+/// Root node of the menu screens
+#[derive(Debug, Copy, Clone, Component)]
+struct MenuScreen;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum MainMenuButton {
+    NewGame,
+    LoadGame,
+    Replays,
+    Difficulty,
+    Statistics,
+    Settings,
+    Quit,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum DifficultyButton {
+    Preset(Difficulty),
+    Custom,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum SettingButton {
+    SafeStart,
+    PracticeMode,
+    Lives,
+    Theme,
+    Animations,
+    LongPressDelay,
+    Controls,
+}
+
+/// Button returning to the main menu
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+struct BackButton;
+
+/// Lives choices of the settings screen, `0` is the fatal bomb rule
+const LIVES_CHOICES: [u8; 4] = [0, 1, 3, 5];
+
+/// Touch long press delay choices of the settings screen, in seconds
+const LONG_PRESS_CHOICES: [f32; 4] = [0.3, 0.5, 0.75, 1.];
+
+fn setup_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Main Menu"))
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Mine Sweeper", font.clone(), 50.));
+            let difficulty_label = format!("Difficulty: {}", *difficulty);
+            for (label, button) in [
+                ("New game", MainMenuButton::NewGame),
+                ("Load game", MainMenuButton::LoadGame),
+                ("Replays", MainMenuButton::Replays),
+                (difficulty_label.as_str(), MainMenuButton::Difficulty),
+                ("Statistics", MainMenuButton::Statistics),
+                ("Settings", MainMenuButton::Settings),
+                ("Quit", MainMenuButton::Quit),
+            ] {
+                ui::spawn_button(parent, font.clone(), label, 400., button);
+            }
+        });
+}
+
+fn main_menu_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    difficulty: Res<Difficulty>,
+    options: Option<Res<BoardOptions>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&MainMenuButton>,
+    mut exit_ewr: EventWriter<AppExit>,
+) {
+    for ButtonActivated(entity) in activated_evr.iter() {
+        let button = match buttons.get(*entity) {
+            Ok(b) => b,
+            Err(_) => continue,
+        };
+        log::debug!("main menu {:?} activated", button);
+        match button {
+            MainMenuButton::NewGame => {
+                commands.insert_resource(crate::board_options(options.as_deref(), *difficulty));
+                state.set(AppState::InGame).unwrap();
+            }
+            MainMenuButton::LoadGame => state.set(AppState::LoadGame).unwrap(),
+            MainMenuButton::Replays => state.set(AppState::Replays).unwrap(),
+            MainMenuButton::Difficulty => state.set(AppState::DifficultyMenu).unwrap(),
+            MainMenuButton::Statistics => state.set(AppState::Statistics).unwrap(),
+            MainMenuButton::Settings => state.set(AppState::Settings).unwrap(),
+            MainMenuButton::Quit => exit_ewr.send(AppExit),
+        }
+        return;
+    }
+}
+
+fn setup_difficulty_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Difficulty Menu"))
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Difficulty", font.clone(), 40.));
+            for difficulty in [
+                Difficulty::Beginner,
+                Difficulty::Intermediate,
+                Difficulty::Expert,
+            ] {
+                let (width, height) = difficulty.map_size();
+                let label = format!(
+                    "{} {}x{}/{}",
+                    difficulty,
+                    width,
+                    height,
+                    difficulty.bomb_count()
+                );
+                ui::spawn_button(
+                    parent,
+                    font.clone(),
+                    &label,
+                    400.,
+                    DifficultyButton::Preset(difficulty),
+                );
+            }
+            ui::spawn_button(parent, font.clone(), "Custom", 400., DifficultyButton::Custom);
+            ui::spawn_button(parent, font.clone(), "Back", 400., BackButton);
+        });
+}
+
+fn difficulty_menu_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    options: Option<Res<BoardOptions>>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&DifficultyButton>,
+    back_buttons: Query<&BackButton>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Out).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        if back_buttons.get(*entity).is_ok() {
+            state.set(AppState::Out).unwrap();
+            return;
+        }
+        match buttons.get(*entity) {
+            Ok(DifficultyButton::Preset(difficulty)) => {
+                log::info!("{} difficulty selected", difficulty);
+                commands.insert_resource(*difficulty);
+                commands.insert_resource(crate::board_options(options.as_deref(), *difficulty));
+                state.set(AppState::Out).unwrap();
+            }
+            Ok(DifficultyButton::Custom) => state.set(AppState::CustomGame).unwrap(),
+            Err(_) => continue,
+        }
+        return;
+    }
+}
+
+fn setup_statistics_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    statistics: Res<Statistics>,
+) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Statistics Screen"))
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Statistics", font.clone(), 40.));
+            for (label, stats) in [
+                ("Beginner", &statistics.beginner),
+                ("Intermediate", &statistics.intermediate),
+                ("Expert", &statistics.expert),
+                ("Custom", &statistics.custom),
+            ] {
+                let best_time = match stats.best_time {
+                    None => "-".to_string(),
+                    Some(t) => format!("{:.1}s", t.as_secs_f32()),
+                };
+                let line = format!(
+                    "{}: {} won / {} played, best {}",
+                    label, stats.won, stats.played, best_time
+                );
+                parent.spawn_bundle(ui::text_bundle(line, font.clone(), 16.));
+            }
+            ui::spawn_button(parent, font.clone(), "Back", 400., BackButton);
+        });
+}
+
+/// Returns to the main menu with a `T` button or Escape
+fn back_button<T: Component>(
+    mut state: ResMut<State<AppState>>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&T>,
+) {
+    let activated = activated_evr.iter().any(|e| buttons.get(e.0).is_ok());
+    if activated || keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Out).unwrap();
+    }
+}
+
+fn setup_settings_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Settings Screen"))
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Settings", font.clone(), 40.));
+            // Labels are filled by `update_settings_labels`
+            for button in [
+                SettingButton::SafeStart,
+                SettingButton::PracticeMode,
+                SettingButton::Lives,
+                SettingButton::Theme,
+                SettingButton::Animations,
+                SettingButton::LongPressDelay,
+                SettingButton::Controls,
+            ] {
+                ui::spawn_button(parent, font.clone(), "", 400., button);
+            }
+            ui::spawn_button(parent, font.clone(), "Back", 400., BackButton);
+        });
+}
+
+fn settings_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut options: ResMut<BoardOptions>,
+    mut active_theme: ResMut<ActiveTheme>,
+    asset_server: Res<AssetServer>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&SettingButton>,
+    back_buttons: Query<&BackButton>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Out).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        if back_buttons.get(*entity).is_ok() {
+            state.set(AppState::Out).unwrap();
+            return;
+        }
+        match buttons.get(*entity) {
+            Ok(SettingButton::SafeStart) => options.safe_start = !options.safe_start,
+            Ok(SettingButton::PracticeMode) => options.practice_mode = !options.practice_mode,
+            Ok(SettingButton::Animations) => options.animations = !options.animations,
+            Ok(SettingButton::Lives) => {
+                let lives = match options.bomb_rule {
+                    BombRule::Fatal => 0,
+                    BombRule::Lives(l) => l,
+                };
+                let next = LIVES_CHOICES
+                    .iter()
+                    .position(|l| *l == lives)
+                    .map_or(0, |i| (i + 1) % LIVES_CHOICES.len());
+                options.bomb_rule = match LIVES_CHOICES[next] {
+                    0 => BombRule::Fatal,
+                    l => BombRule::Lives(l),
+                };
+            }
+            Ok(SettingButton::Controls) => {
+                state.set(AppState::Controls).unwrap();
+                return;
+            }
+            Ok(SettingButton::LongPressDelay) => {
+                let next = LONG_PRESS_CHOICES
+                    .iter()
+                    .position(|d| *d == options.long_press_delay)
+                    .map_or(0, |i| (i + 1) % LONG_PRESS_CHOICES.len());
+                options.long_press_delay = LONG_PRESS_CHOICES[next];
+            }
+            Ok(SettingButton::Theme) => {
+                let current = asset_server
+                    .get_handle_path(&active_theme.0)
+                    .map(|p| p.path().to_string_lossy().into_owned());
+                let next = THEMES
+                    .iter()
+                    .position(|t| Some(*t) == current.as_deref())
+                    .map_or(0, |i| (i + 1) % THEMES.len());
+                active_theme.0 = asset_server.load(THEMES[next]);
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+fn update_settings_labels(
+    options: Res<BoardOptions>,
+    board_assets: Res<BoardAssets>,
+    buttons: Query<(&SettingButton, &Children)>,
+    added_buttons: Query<(), Added<SettingButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    // The theme label changes once the selected theme is loaded
+    if !options.is_changed()
+        && !board_assets.is_changed()
+        && added_buttons.iter().next().is_none()
+    {
+        return;
+    }
+    let on_off = |v: bool| if v { "On" } else { "Off" };
+    for (button, children) in buttons.iter() {
+        let label = match button {
+            SettingButton::SafeStart => format!("Safe start: {}", on_off(options.safe_start)),
+            SettingButton::PracticeMode => {
+                format!("Practice mode: {}", on_off(options.practice_mode))
+            }
+            SettingButton::Lives => match options.bomb_rule {
+                BombRule::Fatal => "Lives: Off".to_string(),
+                BombRule::Lives(l) => format!("Lives: {}", l),
+            },
+            SettingButton::Theme => format!("Theme: {}", board_assets.label),
+            SettingButton::Animations => format!("Animations: {}", on_off(options.animations)),
+            SettingButton::Controls => "Controls".to_string(),
+            SettingButton::LongPressDelay => {
+                format!("Long press: {}s", options.long_press_delay)
+            }
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::layouts;
+use crate::saves::{self, SaveSlot};
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use board_plugin::resources::{ActionInput, Board, GameStatus, InputAction, Replay};
+
+/// Pause screen hiding the board, entered with the pause action or when the window loses focus.
+///
+/// The game can be saved to the manual save slots and its layout exported from it
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(pause_handler)
+            .add_system_set(SystemSet::on_update(AppState::InGame).with_system(focus_handler))
+            .add_system_set(SystemSet::on_enter(AppState::Pause).with_system(setup_pause))
+            .add_system_set(SystemSet::on_update(AppState::Pause).with_system(pause_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Pause).with_system(ui::despawn_screen::<PauseScreen>),
+            );
+    }
+}
+
+/// Root node of the pause screen
+#[derive(Debug, Copy, Clone, Component)]
+struct PauseScreen;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum PauseButton {
+    Resume,
+    Save(SaveSlot),
+    ExportLayout,
+    MainMenu,
+}
+
+/// Toggles the pause with the pause action, Escape or a gamepad start button by default
+fn pause_handler(mut state: ResMut<State<AppState>>, input: ActionInput) {
+    if input.just_pressed(InputAction::Pause) {
+        log::debug!("pause detected");
+        if state.current() == &AppState::InGame {
+            log::info!("entering pause");
+            state.push(AppState::Pause).unwrap();
+        }
+        if state.current() == &AppState::Pause {
+            log::info!("leaving pause");
+            state.pop().unwrap();
+        }
+    }
+}
+
+/// Pauses the game when the window loses focus
+fn focus_handler(mut state: ResMut<State<AppState>>, mut focused_evr: EventReader<WindowFocused>) {
+    if focused_evr.iter().any(|e| !e.focused) {
+        log::info!("window lost focus, entering pause");
+        // An other transition may already be queued this frame
+        if let Err(e) = state.push(AppState::Pause) {
+            log::warn!("Failed to pause: {}", e);
+        }
+    }
+}
+
+fn setup_pause(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = ui::font(&asset_server);
+    // The opaque background hides the board
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Pause Screen"))
+        .insert(PauseScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Paused", font.clone(), 50.));
+            ui::spawn_button(parent, font.clone(), "Resume", 400., PauseButton::Resume);
+            for slot in 1..=saves::MANUAL_SLOTS {
+                let slot = SaveSlot::Manual(slot);
+                let label = format!("Save to {}", slot.label());
+                ui::spawn_button(parent, font.clone(), &label, 400., PauseButton::Save(slot));
+            }
+            ui::spawn_button(
+                parent,
+                font.clone(),
+                "Export layout",
+                400.,
+                PauseButton::ExportLayout,
+            );
+            ui::spawn_button(
+                parent,
+                font.clone(),
+                "Main menu",
+                400.,
+                PauseButton::MainMenu,
+            );
+        });
+}
+
+fn pause_buttons(
+    mut state: ResMut<State<AppState>>,
+    board: Res<Board>,
+    status: Res<GameStatus>,
+    replay: Option<Res<Replay>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<(&PauseButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for ButtonActivated(entity) in activated_evr.iter() {
+        match buttons.get(*entity) {
+            Ok((PauseButton::Resume, _)) => state.pop().unwrap(),
+            // Saving keeps the pause screen, with the updated slot label
+            Ok((PauseButton::Save(slot), children)) => {
+                let saved_game = match saves::running_game(&board, &status, replay.as_deref()) {
+                    Some(s) => s,
+                    None => continue,
+                };
+                if let Err(e) = slot.save(&saved_game) {
+                    log::error!("Failed to save the game: {}", e);
+                    continue;
+                }
+                for child in children.iter() {
+                    if let Ok(mut text) = texts.get_mut(*child) {
+                        text.sections[0].value = format!("Save to {}", slot.label());
+                    }
+                }
+                continue;
+            }
+            Ok((PauseButton::ExportLayout, children)) => {
+                if let Err(e) = layouts::export(&board.tile_map) {
+                    log::error!("Failed to export the layout: {}", e);
+                    continue;
+                }
+                for child in children.iter() {
+                    if let Ok(mut text) = texts.get_mut(*child) {
+                        text.sections[0].value = "Layout exported".to_string();
+                    }
+                }
+                continue;
+            }
+            Ok((PauseButton::MainMenu, _)) => state.replace(AppState::Out).unwrap(),
+            Err(_) => continue,
+        }
+        return;
+    }
+}
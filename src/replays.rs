@@ -0,0 +1,427 @@
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::events::{BoardCompletedEvent, BombExplosionEvent};
+use board_plugin::resources::{Board, Difficulty, Playback, Replay};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Number of recent replays listed in the replays screen
+const LISTED_REPLAYS: usize = 8;
+
+/// Playback speed choices
+const SPEEDS: [f32; 5] = [0.25, 0.5, 1., 2., 4.];
+
+/// Seek step of the playback buttons
+const SEEK_STEP: Duration = Duration::from_secs(5);
+
+/// Replays screen, shown in `AppState::Replays`, and the playback controls of `AppState::Playback`.
+///
+/// The replay of each game is written when it ends
+pub struct ReplaysPlugin;
+
+impl Plugin for ReplaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(save_replay)
+            .add_system_set(SystemSet::on_enter(AppState::Replays).with_system(setup_replays))
+            .add_system_set(SystemSet::on_update(AppState::Replays).with_system(replays_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Replays)
+                    .with_system(ui::despawn_screen::<ReplaysScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Playback).with_system(setup_playback_controls),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playback)
+                    .with_system(playback_buttons)
+                    .with_system(timeline_seek)
+                    .with_system(update_playback_controls),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Playback)
+                    .with_system(ui::despawn_screen::<PlaybackControls>),
+            );
+    }
+}
+
+/// Root node of the replays screen
+#[derive(Debug, Copy, Clone, Component)]
+struct ReplaysScreen;
+
+#[derive(Debug, Clone, Eq, PartialEq, Component)]
+enum ReplaysButton {
+    Replay(PathBuf),
+    Back,
+}
+
+/// Root node of the playback controls
+#[derive(Debug, Copy, Clone, Component)]
+struct PlaybackControls;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+enum PlaybackButton {
+    PlayPause,
+    Slower,
+    Faster,
+    Backward,
+    Forward,
+    Exit,
+}
+
+/// Clickable playback timeline
+#[derive(Debug, Copy, Clone, Component)]
+struct Timeline;
+
+/// Played part of the timeline
+#[derive(Debug, Copy, Clone, Component)]
+struct TimelineProgress;
+
+/// Playback time and speed text
+#[derive(Debug, Copy, Clone, Component)]
+struct PlaybackText;
+
+/// Directory of the replay files, `None` if the platform has no data directory
+pub fn replays_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("bevy_mines").join("replays"))
+}
+
+/// Path of a new replay file, named after the current time
+fn new_replay_path() -> Result<PathBuf, String> {
+    let dir = replays_dir().ok_or_else(|| "no data directory".to_string())?;
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("{}.ron", timestamp.as_secs())))
+}
+
+/// Reads the replay file at `path`
+fn load(path: &PathBuf) -> Result<Replay, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let replay: Replay = ron::from_str(&content).map_err(|e| e.to_string())?;
+    replay.validate()?;
+    Ok(replay)
+}
+
+/// Paths of the most recent replay files, newest first
+fn recent_replays() -> Vec<PathBuf> {
+    let entries = match replays_dir().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return Vec::new(),
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    // Files are named after their creation time
+    paths.sort();
+    paths.reverse();
+    paths.truncate(LISTED_REPLAYS);
+    paths
+}
+
+/// Writes `replay` to `path`
+fn save(replay: &Replay, path: &PathBuf) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let content = ron::to_string(replay).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())?;
+    log::info!("Saved replay to {}", path.display());
+    Ok(())
+}
+
+/// Writes the replay of the running game when it ends.
+///
+/// A game continued by undoing its last move overwrites its replay when it ends again
+fn save_replay(
+    board: Option<Res<Board>>,
+    replay: Option<Res<Replay>>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut replay_file: Local<Option<(Entity, PathBuf)>>,
+) {
+    let ended = board_completed_evr.iter().count() + bomb_explosion_evr.iter().count() > 0;
+    let (board, replay) = match (board, replay) {
+        (Some(b), Some(r)) if ended => (b, r),
+        _ => return,
+    };
+    let path = match replay_file.as_ref() {
+        Some((entity, path)) if *entity == board.entity => path.clone(),
+        _ => match new_replay_path() {
+            Ok(path) => path,
+            Err(e) => {
+                log::error!("Failed to name the replay file: {}", e);
+                return;
+            }
+        },
+    };
+    if let Err(e) = save(&replay, &path) {
+        log::error!("Failed to save the replay: {}", e);
+        return;
+    }
+    *replay_file = Some((board.entity, path));
+}
+
+fn setup_replays(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = ui::font(&asset_server);
+    let replays: Vec<(PathBuf, String)> = recent_replays()
+        .into_iter()
+        .filter_map(|path| match load(&path) {
+            Ok(replay) => {
+                let label = format!(
+                    "{} {:.1}s",
+                    Difficulty::from(&replay.options),
+                    replay.duration.as_secs_f32()
+                );
+                Some((path, label))
+            }
+            Err(e) => {
+                log::warn!("Skipping broken replay {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect();
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Replays Screen"))
+        .insert(ReplaysScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Replays", font.clone(), 40.));
+            if replays.is_empty() {
+                parent.spawn_bundle(ui::text_bundle("No replay yet", font.clone(), 20.));
+            }
+            for (path, label) in replays {
+                ui::spawn_sized_button(
+                    parent,
+                    font.clone(),
+                    &label,
+                    Vec2::new(400., 30.),
+                    15.,
+                    ReplaysButton::Replay(path),
+                );
+            }
+            ui::spawn_button(parent, font.clone(), "Back", 400., ReplaysButton::Back);
+        });
+}
+
+fn replays_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&ReplaysButton>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Out).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        match buttons.get(*entity) {
+            Ok(ReplaysButton::Replay(path)) => match load(path) {
+                Ok(replay) => {
+                    log::info!("playing back {}", path.display());
+                    commands.insert_resource(Playback::new(replay));
+                    state.set(AppState::Playback).unwrap();
+                }
+                Err(e) => {
+                    log::error!("Failed to load replay {}: {}", path.display(), e);
+                    continue;
+                }
+            },
+            Ok(ReplaysButton::Back) => state.set(AppState::Out).unwrap(),
+            Err(_) => continue,
+        }
+        return;
+    }
+}
+
+fn setup_playback_controls(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(0.),
+                    bottom: Val::Px(0.),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.1, 0.1, 0.1, 0.8).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Playback Controls"))
+        .insert(PlaybackControls)
+        .with_children(|parent| {
+            // Labels are filled by `update_playback_controls`
+            parent
+                .spawn_bundle(ui::text_bundle("", font.clone(), 15.))
+                .insert(PlaybackText);
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(90.), Val::Px(12.)),
+                        margin: Rect::all(Val::Px(5.)),
+                        ..Default::default()
+                    },
+                    color: ui::NORMAL_BUTTON.into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("Timeline"))
+                .insert(Timeline)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..Default::default()
+                            },
+                            color: ui::TEXT_COLOR.into(),
+                            ..Default::default()
+                        })
+                        .insert(TimelineProgress);
+                });
+            parent
+                .spawn_bundle(ui::row_bundle())
+                .with_children(|parent| {
+                    for (label, button) in [
+                        ("", PlaybackButton::PlayPause),
+                        ("Slower", PlaybackButton::Slower),
+                        ("Faster", PlaybackButton::Faster),
+                        ("-5s", PlaybackButton::Backward),
+                        ("+5s", PlaybackButton::Forward),
+                        ("Exit", PlaybackButton::Exit),
+                    ] {
+                        ui::spawn_sized_button(
+                            parent,
+                            font.clone(),
+                            label,
+                            Vec2::new(100., 30.),
+                            12.,
+                            button,
+                        );
+                    }
+                });
+        });
+}
+
+fn playback_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut playback: ResMut<Playback>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&PlaybackButton>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Replays).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        let speed = SPEEDS
+            .iter()
+            .position(|s| *s == playback.speed)
+            .unwrap_or(2);
+        match buttons.get(*entity) {
+            Ok(PlaybackButton::PlayPause) if playback.is_finished() => {
+                playback.seek(Duration::ZERO);
+                playback.paused = false;
+            }
+            Ok(PlaybackButton::PlayPause) => playback.paused = !playback.paused,
+            Ok(PlaybackButton::Slower) => playback.speed = SPEEDS[speed.saturating_sub(1)],
+            Ok(PlaybackButton::Faster) => {
+                playback.speed = SPEEDS[(speed + 1).min(SPEEDS.len() - 1)]
+            }
+            Ok(PlaybackButton::Backward) => {
+                let time = playback.time().saturating_sub(SEEK_STEP);
+                playback.seek(time);
+            }
+            Ok(PlaybackButton::Forward) => {
+                let time = playback.time() + SEEK_STEP;
+                playback.seek(time);
+            }
+            Ok(PlaybackButton::Exit) => {
+                state.set(AppState::Replays).unwrap();
+                return;
+            }
+            Err(_) => continue,
+        }
+    }
+}
+
+/// Seeks to the clicked timeline position, following the cursor while the button is held
+fn timeline_seek(
+    windows: Res<Windows>,
+    mut playback: ResMut<Playback>,
+    timelines: Query<(&Interaction, &Node, &GlobalTransform), With<Timeline>>,
+    mut scrubbed: Local<Option<f32>>,
+) {
+    let (interaction, node, transform) = match timelines.get_single() {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+    let cursor = windows.get_primary().and_then(|w| w.cursor_position());
+    let cursor = match (interaction, cursor) {
+        (Interaction::Clicked, Some(c)) => c,
+        _ => {
+            *scrubbed = None;
+            return;
+        }
+    };
+    // UI nodes are positioned by their center
+    let left = transform.translation.x - node.size.x / 2.;
+    let fraction = ((cursor.x - left) / node.size.x).clamp(0., 1.);
+    if *scrubbed == Some(fraction) {
+        return;
+    }
+    *scrubbed = Some(fraction);
+    let time = playback.replay.duration.mul_f32(fraction);
+    playback.seek(time);
+}
+
+fn update_playback_controls(
+    playback: Res<Playback>,
+    buttons: Query<(&PlaybackButton, &Children)>,
+    mut progress: Query<&mut Style, With<TimelineProgress>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<PlaybackText>>,
+        QueryState<&mut Text, Without<PlaybackText>>,
+    )>,
+) {
+    if !playback.is_changed() {
+        return;
+    }
+    let duration = playback.replay.duration.as_secs_f32();
+    let time = playback.time().as_secs_f32();
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = format!("{:.1}s / {:.1}s  x{}", time, duration, playback.speed);
+    }
+    for mut style in progress.iter_mut() {
+        let percent = match duration > 0. {
+            true => time / duration * 100.,
+            false => 100.,
+        };
+        style.size.width = Val::Percent(percent);
+    }
+    let label = match (playback.is_finished(), playback.paused) {
+        (true, _) => "Replay",
+        (false, true) => "Play",
+        (false, false) => "Pause",
+    };
+    for (button, children) in buttons.iter() {
+        if *button != PlaybackButton::PlayPause {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = texts.q1().get_mut(*child) {
+                text.sections[0].value = label.to_string();
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::autosave;
+use crate::layouts;
+use crate::ui::{self, ButtonActivated};
+use crate::AppState;
+use bevy::app::AppExit;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{Board, Difficulty, GameStatus, Playback, Replay, SavedGame};
+use std::fs;
+use std::path::PathBuf;
+
+/// Number of manual save slots
+pub const MANUAL_SLOTS: u8 = 3;
+
+/// Directory of the save files, `None` if the platform has no data directory
+pub fn saves_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("bevy_mines").join("saves"))
+}
+
+/// Saved game file, in the `bevy_mines` user data directory
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SaveSlot {
+    /// Game saved on quit and resumed on the next launch
+    Quit,
+    /// Slot saved from the pause screen, numbered from 1
+    Manual(u8),
+}
+
+impl SaveSlot {
+    /// Path of the save file, `None` if the platform has no data directory
+    pub fn path(&self) -> Option<PathBuf> {
+        let file = match self {
+            Self::Quit => "quit.ron".to_string(),
+            Self::Manual(slot) => format!("slot{}.ron", slot),
+        };
+        saves_dir().map(|dir| dir.join(file))
+    }
+
+    /// Reads the saved game of the slot
+    pub fn load(&self) -> Result<SavedGame, String> {
+        let path = self.path().ok_or_else(|| "no data directory".to_string())?;
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let saved_game: SavedGame = ron::from_str(&content).map_err(|e| e.to_string())?;
+        saved_game.validate()?;
+        log::info!("Loaded game from {}", path.display());
+        Ok(saved_game)
+    }
+
+    /// Writes `saved_game` to the slot, replacing the previous one
+    pub fn save(&self, saved_game: &SavedGame) -> Result<(), String> {
+        let path = self.path().ok_or_else(|| "no data directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = ron::to_string(saved_game).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| e.to_string())?;
+        log::info!("Saved game to {}", path.display());
+        Ok(())
+    }
+
+    /// Removes the save file of the slot
+    pub fn delete(&self) -> Result<(), String> {
+        let path = self.path().ok_or_else(|| "no data directory".to_string())?;
+        fs::remove_file(&path).map_err(|e| e.to_string())
+    }
+
+    /// Button label describing the slot content
+    pub fn label(&self) -> String {
+        let name = match self {
+            Self::Quit => "Last game".to_string(),
+            Self::Manual(slot) => format!("Slot {}", slot),
+        };
+        match self.load() {
+            Ok(saved_game) => format!(
+                "{}: {} {:.0}s",
+                name,
+                Difficulty::from(&saved_game.options),
+                saved_game.elapsed.as_secs_f32()
+            ),
+            Err(_) => format!("{}: empty", name),
+        }
+    }
+}
+
+/// Snapshot of the running game, `None` if it is over or nothing was played yet
+pub fn running_game(
+    board: &Board,
+    status: &GameStatus,
+    replay: Option<&Replay>,
+) -> Option<SavedGame> {
+    if board.is_completed() || board.is_lost() || board.history.moves().is_empty() {
+        return None;
+    }
+    Some(SavedGame::new(board, status, replay))
+}
+
+/// Load game screen, shown in `AppState::LoadGame` with the saves and the exported layouts, and the
+/// save on quit of the running game
+pub struct SavesPlugin;
+
+impl Plugin for SavesPlugin {
+    fn build(&self, app: &mut App) {
+        // The exit is handled in the last stage to save in the frame it is requested
+        app.add_system_to_stage(CoreStage::Last, save_on_quit)
+            .add_system_set(SystemSet::on_enter(AppState::LoadGame).with_system(setup_load_game))
+            .add_system_set(SystemSet::on_update(AppState::LoadGame).with_system(load_game_buttons))
+            .add_system_set(
+                SystemSet::on_exit(AppState::LoadGame)
+                    .with_system(ui::despawn_screen::<LoadGameScreen>),
+            );
+    }
+}
+
+/// Root node of the load game screen
+#[derive(Debug, Copy, Clone, Component)]
+struct LoadGameScreen;
+
+#[derive(Debug, Clone, Eq, PartialEq, Component)]
+enum LoadGameButton {
+    Slot(SaveSlot),
+    /// Layout file started as a new game
+    Layout(PathBuf),
+    Back,
+}
+
+/// Saves the running game to the quit slot when the app exits, which replaces its autosave
+fn save_on_quit(
+    mut exit_evr: EventReader<AppExit>,
+    board: Option<Res<Board>>,
+    status: Option<Res<GameStatus>>,
+    replay: Option<Res<Replay>>,
+    playback: Option<Res<Playback>>,
+) {
+    if exit_evr.iter().next().is_none() {
+        return;
+    }
+    let saved_game = match (board, status, playback) {
+        (Some(board), Some(status), None) => running_game(&board, &status, replay.as_deref()),
+        _ => None,
+    };
+    if let Some(saved_game) = saved_game {
+        if let Err(e) = SaveSlot::Quit.save(&saved_game) {
+            // The autosave is kept to be restored instead
+            log::error!("Failed to save the game on quit: {}", e);
+            return;
+        }
+    }
+    autosave::delete();
+}
+
+/// Resumes the game saved on quit, returning `false` if there is none. The save is removed so it
+/// is only resumed once
+pub fn resume_on_launch(commands: &mut Commands, state: &mut State<AppState>) -> bool {
+    let saved_game = match SaveSlot::Quit.load() {
+        Ok(s) => s,
+        Err(e) => {
+            log::debug!("No game to resume: {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = SaveSlot::Quit.delete() {
+        log::warn!("Failed to remove the resumed save: {}", e);
+    }
+    log::info!("resuming the game saved on quit");
+    start_saved_game(commands, state, saved_game);
+    true
+}
+
+/// Starts a game from `saved_game`, the board keeps its options without changing the player's ones
+pub fn start_saved_game(
+    commands: &mut Commands,
+    state: &mut State<AppState>,
+    saved_game: SavedGame,
+) {
+    commands.insert_resource(saved_game);
+    state.set(AppState::InGame).unwrap();
+}
+
+fn setup_load_game(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = ui::font(&asset_server);
+    commands
+        .spawn_bundle(ui::screen_bundle(Color::rgb(0.1, 0.1, 0.1)))
+        .insert(Name::new("Load Game Screen"))
+        .insert(LoadGameScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(ui::text_bundle("Load game", font.clone(), 40.));
+            for slot in 1..=MANUAL_SLOTS {
+                let slot = SaveSlot::Manual(slot);
+                ui::spawn_button(
+                    parent,
+                    font.clone(),
+                    &slot.label(),
+                    400.,
+                    LoadGameButton::Slot(slot),
+                );
+            }
+            for path in layouts::recent_layouts() {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let label = format!("Layout: {}", name);
+                ui::spawn_button(
+                    parent,
+                    font.clone(),
+                    &label,
+                    400.,
+                    LoadGameButton::Layout(path),
+                );
+            }
+            ui::spawn_button(parent, font.clone(), "Back", 400., LoadGameButton::Back);
+        });
+}
+
+fn load_game_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    keys: Res<Input<KeyCode>>,
+    mut activated_evr: EventReader<ButtonActivated>,
+    buttons: Query<&LoadGameButton>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Out).unwrap();
+        return;
+    }
+    for ButtonActivated(entity) in activated_evr.iter() {
+        match buttons.get(*entity) {
+            Ok(LoadGameButton::Slot(slot)) => match slot.load() {
+                Ok(saved_game) => start_saved_game(&mut commands, &mut state, saved_game),
+                Err(e) => {
+                    log::info!("Nothing to load from {:?}: {}", slot, e);
+                    continue;
+                }
+            },
+            Ok(LoadGameButton::Layout(path)) => {
+                let started = layouts::load(path).and_then(|tile_map| {
+                    layouts::start_layout_game(&mut commands, &mut state, tile_map)
+                });
+                if let Err(e) = started {
+                    log::error!("Failed to load layout {}: {}", path.display(), e);
+                    continue;
+                }
+            }
+            Ok(LoadGameButton::Back) => state.set(AppState::Out).unwrap(),
+            Err(_) => continue,
+        }
+        return;
+    }
+}
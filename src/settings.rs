@@ -0,0 +1,122 @@
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{ActiveTheme, BoardOptions, Difficulty, InputBindings};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Settings file name, in the `bevy_mines` user config directory
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Selectable board theme files
+pub const THEMES: [&str; 3] = [
+    "themes/default.theme.ron",
+    "themes/night.theme.ron",
+    "themes/classic.theme.ron",
+];
+
+/// Player preferences persisted in the settings file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub board_options: BoardOptions,
+    /// Board theme file path
+    pub theme: String,
+    pub input_bindings: InputBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let difficulty = Difficulty::default();
+        Self {
+            difficulty,
+            board_options: crate::board_options(None, difficulty),
+            theme: THEMES[0].to_string(),
+            input_bindings: Default::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Path of the settings file, `None` if the platform has no config directory
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("bevy_mines").join(SETTINGS_FILE))
+    }
+
+    /// Loads the settings file, falling back to the default settings if it is missing or broken
+    pub fn load() -> Self {
+        let path = match Self::path() {
+            None => {
+                log::warn!("No config directory, using default settings");
+                return Self::default();
+            }
+            Some(p) => p,
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                log::info!("No settings loaded from {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        match ron::from_str(&content) {
+            Ok(settings) => {
+                log::info!("Loaded settings from {}", path.display());
+                settings
+            }
+            Err(e) => {
+                log::warn!(
+                    "Broken settings file {}, using default settings: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the settings file
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or_else(|| "no config directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| e.to_string())?;
+        log::info!("Saved settings to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Saves the settings file when the preferences change
+pub fn save_settings(
+    asset_server: Res<AssetServer>,
+    board_options: Res<BoardOptions>,
+    difficulty: Res<Difficulty>,
+    active_theme: Res<ActiveTheme>,
+    input_bindings: Res<InputBindings>,
+) {
+    // Resources inserted at startup are not saved back
+    let board_options_changed = board_options.is_changed() && !board_options.is_added();
+    let difficulty_changed = difficulty.is_changed() && !difficulty.is_added();
+    let theme_changed = active_theme.is_changed() && !active_theme.is_added();
+    let bindings_changed = input_bindings.is_changed() && !input_bindings.is_added();
+    if !board_options_changed && !difficulty_changed && !theme_changed && !bindings_changed {
+        return;
+    }
+    let theme = match asset_server.get_handle_path(&active_theme.0) {
+        Some(path) => path.path().to_string_lossy().into_owned(),
+        None => THEMES[0].to_string(),
+    };
+    let settings = Settings {
+        difficulty: *difficulty,
+        board_options: board_options.clone(),
+        theme,
+        input_bindings: input_bindings.clone(),
+    };
+    if let Err(e) = settings.save() {
+        log::error!("Failed to save settings: {}", e);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::settings::Settings;
+use bevy::log;
+use board_plugin::resources::Difficulty;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Statistics file name, next to the settings file
+const STATISTICS_FILE: &str = "statistics.ron";
+
+/// Results of the games played at a given difficulty
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyStatistics {
+    pub played: u32,
+    pub won: u32,
+    /// Fastest win, games using undo or practice mode are excluded
+    pub best_time: Option<Duration>,
+}
+
+/// Game statistics per difficulty, custom boards are grouped. Must be used as a resource
+// We use serde to persist the statistics in their own file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub beginner: DifficultyStatistics,
+    pub intermediate: DifficultyStatistics,
+    pub expert: DifficultyStatistics,
+    pub custom: DifficultyStatistics,
+}
+
+impl Statistics {
+    /// Path of the statistics file, `None` if the platform has no config directory
+    pub fn path() -> Option<PathBuf> {
+        Settings::path().map(|path| path.with_file_name(STATISTICS_FILE))
+    }
+
+    /// Loads the statistics file, starting from empty statistics if it is missing or broken
+    pub fn load() -> Self {
+        let path = match Self::path() {
+            None => {
+                log::warn!("No config directory, statistics are not kept");
+                return Self::default();
+            }
+            Some(p) => p,
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                log::info!("No statistics loaded from {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        match ron::from_str(&content) {
+            Ok(statistics) => {
+                log::info!("Loaded statistics from {}", path.display());
+                statistics
+            }
+            Err(e) => {
+                log::warn!(
+                    "Broken statistics file {}, starting new statistics: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the statistics file
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or_else(|| "no config directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content =
+            ron::ser::to_string_pretty(self, Default::default()).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| e.to_string())?;
+        log::info!("Saved statistics to {}", path.display());
+        Ok(())
+    }
+
+    pub fn get(&self, difficulty: &Difficulty) -> &DifficultyStatistics {
+        match difficulty {
+            Difficulty::Beginner => &self.beginner,
+            Difficulty::Intermediate => &self.intermediate,
+            Difficulty::Expert => &self.expert,
+            Difficulty::Custom { .. } => &self.custom,
+        }
+    }
+
+    pub fn get_mut(&mut self, difficulty: &Difficulty) -> &mut DifficultyStatistics {
+        match difficulty {
+            Difficulty::Beginner => &mut self.beginner,
+            Difficulty::Intermediate => &mut self.intermediate,
+            Difficulty::Expert => &mut self.expert,
+            Difficulty::Custom { .. } => &mut self.custom,
+        }
+    }
+
+    /// Records a finished game, returning `true` if it is a new best time
+    pub fn record(
+        &mut self,
+        difficulty: &Difficulty,
+        won: bool,
+        time: Duration,
+        eligible: bool,
+    ) -> bool {
+        let stats = self.get_mut(difficulty);
+        stats.played += 1;
+        if !won {
+            return false;
+        }
+        stats.won += 1;
+        if !eligible || stats.best_time.is_some_and(|best| best <= time) {
+            return false;
+        }
+        stats.best_time = Some(time);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn record_keeps_the_best_eligible_time() {
+        let mut statistics = Statistics::default();
+        let expert = Difficulty::Expert;
+        assert!(!statistics.record(&expert, false, secs(10), true));
+        assert!(statistics.record(&expert, true, secs(90), true));
+        assert!(!statistics.record(&expert, true, secs(60), false));
+        assert!(!statistics.record(&expert, true, secs(120), true));
+        assert!(statistics.record(&expert, true, secs(80), true));
+        assert_eq!(
+            statistics.expert,
+            DifficultyStatistics {
+                played: 5,
+                won: 4,
+                best_time: Some(secs(80)),
+            }
+        );
+        // Custom boards share their statistics
+        let custom = Difficulty::Custom {
+            width: 10,
+            height: 10,
+            bomb_count: 10,
+        };
+        statistics.record(&custom, true, secs(5), true);
+        assert_eq!(statistics.get(&custom), &statistics.custom);
+        assert_eq!(statistics.custom.played, 1);
+    }
+
+    #[test]
+    fn saved_statistics_round_trip() {
+        let mut statistics = Statistics::default();
+        statistics.record(
+            &Difficulty::Beginner,
+            true,
+            Duration::from_millis(12_345),
+            true,
+        );
+        statistics.record(&Difficulty::Intermediate, false, secs(3), true);
+        let saved = ron::ser::to_string_pretty(&statistics, Default::default()).unwrap();
+        assert_eq!(ron::from_str::<Statistics>(&saved).unwrap(), statistics);
+    }
+
+    #[test]
+    fn missing_statistics_start_empty() {
+        let statistics: Statistics = ron::from_str("(expert: (played: 3))").unwrap();
+        assert_eq!(statistics.expert.played, 3);
+        assert_eq!(statistics.expert.best_time, None);
+        assert_eq!(statistics.beginner, DifficultyStatistics::default());
+    }
+}
@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+
+pub const TEXT_COLOR: Color = Color::WHITE;
+pub const NORMAL_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+pub const HOVERED_BUTTON: Color = Color::rgb(0.35, 0.35, 0.35);
+pub const PRESSED_BUTTON: Color = Color::rgb(0.45, 0.45, 0.55);
+pub const DISABLED_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const FOCUSED_BUTTON: Color = Color::rgb(0.35, 0.35, 0.5);
+
+/// Marks a button which can't be clicked
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct Disabled;
+
+/// Marks the button selected by keyboard navigation
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub struct Focused;
+
+/// A button was clicked or validated with the keyboard
+#[derive(Debug, Copy, Clone)]
+pub struct ButtonActivated(pub Entity);
+
+/// Menu widgets: button colors, mouse and keyboard navigation
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ButtonActivated>()
+            .add_system(button_colors)
+            .add_system(mouse_activation)
+            .add_system(keyboard_navigation);
+    }
+}
+
+/// Menu font
+pub fn font(asset_server: &AssetServer) -> Handle<Font> {
+    asset_server.load("fonts/pixeled.ttf")
+}
+
+/// Full screen root node, laying its children from top to bottom
+pub fn screen_bundle(color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: color.into(),
+        ..Default::default()
+    }
+}
+
+/// Transparent node laying its children from left to right
+pub fn row_bundle() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            margin: Rect::all(Val::Px(5.)),
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }
+}
+
+/// Transparent node laying its children from top to bottom
+pub fn column_bundle() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            margin: Rect::all(Val::Px(5.)),
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }
+}
+
+/// Text bundle with the menu style
+pub fn text_bundle(value: impl Into<String>, font: Handle<Font>, font_size: f32) -> TextBundle {
+    TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font,
+                font_size,
+                color: TEXT_COLOR,
+            },
+            Default::default(),
+        ),
+        style: Style {
+            margin: Rect::all(Val::Px(5.)),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Spawns a labelled button with its `marker` component
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: Handle<Font>,
+    label: &str,
+    width: f32,
+    marker: impl Component,
+) -> Entity {
+    spawn_sized_button(parent, font, label, Vec2::new(width, 40.), 20., marker)
+}
+
+/// Spawns a labelled button of the given `size` and `font_size` with its `marker` component
+pub fn spawn_sized_button(
+    parent: &mut ChildBuilder,
+    font: Handle<Font>,
+    label: &str,
+    size: Vec2,
+    font_size: f32,
+    marker: impl Component,
+) -> Entity {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                margin: Rect::all(Val::Px(5.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(marker)
+        .with_children(|parent| {
+            parent.spawn_bundle(text_bundle(label, font, font_size));
+        })
+        .id()
+}
+
+/// Colors the buttons according to their interaction and focus
+fn button_colors(
+    mut buttons: Query<
+        (&Interaction, &mut UiColor, Option<&Focused>),
+        (With<Button>, Without<Disabled>),
+    >,
+) {
+    for (interaction, mut color, focused) in buttons.iter_mut() {
+        let new_color = match (interaction, focused) {
+            (Interaction::Clicked, _) => PRESSED_BUTTON,
+            (Interaction::Hovered, _) => HOVERED_BUTTON,
+            (Interaction::None, Some(_)) => FOCUSED_BUTTON,
+            (Interaction::None, None) => NORMAL_BUTTON,
+        };
+        if color.0 != new_color {
+            color.0 = new_color;
+        }
+    }
+}
+
+/// Sends `ButtonActivated` for clicked buttons
+fn mouse_activation(
+    buttons: Query<(Entity, &Interaction), (Changed<Interaction>, Without<Disabled>)>,
+    mut activated_ewr: EventWriter<ButtonActivated>,
+) {
+    for (entity, interaction) in buttons.iter() {
+        if *interaction == Interaction::Clicked {
+            activated_ewr.send(ButtonActivated(entity));
+        }
+    }
+}
+
+/// Moves the focus between buttons with the arrow keys and activates it with Enter or Space
+fn keyboard_navigation(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    buttons: Query<(Entity, &GlobalTransform, Option<&Focused>), (With<Button>, Without<Disabled>)>,
+    mut activated_ewr: EventWriter<ButtonActivated>,
+) {
+    let focused = buttons
+        .iter()
+        .find(|(_, _, f)| f.is_some())
+        .map(|(e, t, _)| (e, t.translation.truncate()));
+    if let Some((entity, _)) = focused {
+        if keys.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+            activated_ewr.send(ButtonActivated(entity));
+            return;
+        }
+    }
+    // UI space is y-up
+    let direction = if keys.just_pressed(KeyCode::Up) {
+        Vec2::Y
+    } else if keys.just_pressed(KeyCode::Down) {
+        -Vec2::Y
+    } else if keys.just_pressed(KeyCode::Left) {
+        -Vec2::X
+    } else if keys.just_pressed(KeyCode::Right) {
+        Vec2::X
+    } else {
+        return;
+    };
+    let target = match focused {
+        // Without focus we start from the top left button
+        None => buttons
+            .iter()
+            .map(|(e, t, _)| (e, t.translation.truncate()))
+            .min_by(|(_, a), (_, b)| (a.x - a.y).total_cmp(&(b.x - b.y)))
+            .map(|(e, _)| e),
+        // The closest button in the given direction, favoring aligned ones
+        Some((current, origin)) => buttons
+            .iter()
+            .filter(|(e, _, _)| *e != current)
+            .filter_map(|(e, t, _)| {
+                let delta = t.translation.truncate() - origin;
+                let along = delta.dot(direction);
+                if along <= 0. {
+                    return None;
+                }
+                let across = (delta - direction * along).length();
+                Some((e, along + across * 2.))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(e, _)| e),
+    };
+    if let Some(target) = target {
+        if let Some((current, _)) = focused {
+            commands.entity(current).remove::<Focused>();
+        }
+        commands.entity(target).insert(Focused);
+    }
+}
+
+/// Despawns every entity with the `T` component, used to clear a screen
+pub fn despawn_screen<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}